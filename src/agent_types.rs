@@ -0,0 +1,307 @@
+//! Who is who among the subagents: the label, colour, home station and
+//! character for each `subagent_type`.
+//!
+//! Types listed under `[[agents.types]]` in the config come first, then the
+//! built-in ones. A type matched by neither still gets an identity of its own,
+//! worked out from its name, so it looks the same every run.
+
+use bevy::prelude::*;
+
+use crate::components::StationType;
+use crate::config::AgentTypeConfig;
+
+/// How an agent is drawn and where it works when idle
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentType {
+    id: String,
+    label: String,
+    color: Color,
+    home_station: StationType,
+    sprite: Option<String>,
+}
+
+impl AgentType {
+    /// The agent of the session itself
+    pub fn main() -> Self {
+        AgentType {
+            id: "main".to_string(),
+            label: "Main".to_string(),
+            color: Color::srgb(0.2, 0.6, 1.0),
+            home_station: StationType::Center,
+            sprite: None,
+        }
+    }
+
+    /// Registry entry this type came from: the lowercased pattern, or the name for generated types
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    /// Where the agent does its primary work
+    pub fn home_station(&self) -> StationType {
+        self.home_station
+    }
+
+    /// Character to draw, by name; `None` leaves the choice to `SpriteAssets`
+    pub fn sprite(&self) -> Option<&str> {
+        self.sprite.as_deref()
+    }
+
+    /// An identity derived from `name` alone
+    fn generated(name: &str) -> Self {
+        let hash = stable_hash(name);
+        AgentType {
+            id: name.to_string(),
+            label: short_label(name),
+            color: Color::hsl((hash % 360) as f32, 0.65, 0.55),
+            home_station: guess_station(name)
+                .unwrap_or(GENERATED_STATIONS[(hash >> 16) as usize % GENERATED_STATIONS.len()]),
+            sprite: None,
+        }
+    }
+}
+
+/// Types every Claude Code install knows: pattern, label, colour and home station
+const BUILTIN: &[(&str, &str, [f32; 3], StationType)] = &[
+    ("explore", "Explore", [0.2, 0.8, 0.4], StationType::Library),
+    ("plan", "Plan", [0.7, 0.3, 0.9], StationType::Desk),
+    ("bash", "Bash", [1.0, 0.6, 0.2], StationType::Terminal),
+    ("code-reviewer", "Review", [1.0, 0.3, 0.3], StationType::Library),
+    ("ui-ux-reviewer", "UI/UX", [1.0, 0.8, 0.2], StationType::Desk),
+    ("statusline-setup", "Setup", [0.8, 0.2, 0.2], StationType::Terminal),
+    ("claude-code-guide", "Guide", [0.4, 0.8, 0.4], StationType::Library),
+    // Fast, stays central
+    ("haiku", "Haiku", [0.6, 0.6, 0.6], StationType::Center),
+    ("devops-engineer", "DevOps", [0.3, 0.8, 0.9], StationType::Terminal),
+    ("security-analyst", "Security", [0.2, 0.3, 0.6], StationType::Library),
+    ("project-manager", "PM", [0.4, 0.5, 0.3], StationType::MeetingArea),
+    ("general-purpose", "Agent", [0.9, 0.8, 0.3], StationType::Center),
+];
+
+/// Words in a type's name that give away where it works, checked in order
+const STATION_HINTS: &[(&str, StationType)] = &[
+    ("explor", StationType::Library),
+    ("review", StationType::Library),
+    ("research", StationType::Library),
+    ("analy", StationType::Library),
+    ("search", StationType::Library),
+    ("audit", StationType::Library),
+    ("doc", StationType::Library),
+    ("plan", StationType::Desk),
+    ("writ", StationType::Desk),
+    ("design", StationType::Desk),
+    ("architect", StationType::Desk),
+    ("refactor", StationType::Desk),
+    ("test", StationType::Terminal),
+    ("build", StationType::Terminal),
+    ("deploy", StationType::Terminal),
+    ("ops", StationType::Terminal),
+    ("shell", StationType::Terminal),
+    ("migrat", StationType::Terminal),
+    ("web", StationType::WebPortal),
+    ("fetch", StationType::WebPortal),
+    ("brows", StationType::WebPortal),
+    ("manag", StationType::MeetingArea),
+    ("coordinat", StationType::MeetingArea),
+    ("lead", StationType::MeetingArea),
+    ("orchestr", StationType::MeetingArea),
+];
+
+/// Stations a generated type without a telling name can get
+const GENERATED_STATIONS: [StationType; 5] = [
+    StationType::Library,
+    StationType::Desk,
+    StationType::Terminal,
+    StationType::WebPortal,
+    StationType::MeetingArea,
+];
+
+/// Subagent types, configured ones first, then the built-in ones
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentTypes {
+    entries: Vec<AgentTypeConfig>,
+}
+
+impl Default for AgentTypes {
+    fn default() -> Self {
+        AgentTypes::new(&[])
+    }
+}
+
+impl AgentTypes {
+    pub fn new(configured: &[AgentTypeConfig]) -> Self {
+        let configured = configured.iter().map(|entry| AgentTypeConfig {
+            pattern: entry.pattern.to_lowercase(),
+            ..entry.clone()
+        });
+        let builtin = BUILTIN.iter().map(|&(pattern, label, color, station)| AgentTypeConfig {
+            pattern: pattern.to_string(),
+            label: Some(label.to_string()),
+            color: Some(color),
+            home_station: Some(station),
+            sprite: None,
+        });
+        AgentTypes {
+            entries: configured.chain(builtin).collect(),
+        }
+    }
+
+    /// Ids of the built-in types
+    pub fn builtin_ids() -> impl Iterator<Item = &'static str> {
+        BUILTIN.iter().map(|(pattern, ..)| *pattern)
+    }
+
+    /// The type for a Task's `subagent_type`; never fails, unknown names get a generated identity
+    pub fn resolve(&self, subagent_type: &str) -> AgentType {
+        let name = subagent_type.to_lowercase();
+        let Some(entry) = self.entries.iter().find(|entry| matches(&entry.pattern, &name)) else {
+            return AgentType::generated(&name);
+        };
+
+        // Whatever the entry leaves out comes from its pattern, so every match looks alike
+        let generated = AgentType::generated(&entry.pattern);
+        AgentType {
+            id: entry.pattern.clone(),
+            label: entry.label.clone().unwrap_or(generated.label),
+            color: entry
+                .color
+                .map_or(generated.color, |[red, green, blue]| Color::srgb(red, green, blue)),
+            home_station: entry.home_station.unwrap_or(generated.home_station),
+            sprite: entry.sprite.clone(),
+        }
+    }
+}
+
+/// Whether `name` fits `pattern`, where `*` matches any run of characters
+fn matches(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No `*` at all
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// FNV-1a, which unlike `DefaultHasher` is the same across Rust releases
+pub(crate) fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// A label short enough for the name tag: initials, then the last word in full
+fn short_label(name: &str) -> String {
+    let words: Vec<&str> = name
+        .split(['-', '_', ' ', '*'])
+        .filter(|word| !word.is_empty())
+        .collect();
+    let Some((last, rest)) = words.split_last() else {
+        return "Agent".to_string();
+    };
+
+    let capitalise = |word: &str| {
+        let mut chars = word.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+            .unwrap_or_default()
+    };
+    let initials: String = rest.iter().filter_map(|word| word.chars().next()).collect();
+    let label = initials.to_uppercase() + &capitalise(last);
+    label.chars().take(9).collect()
+}
+
+fn guess_station(name: &str) -> Option<StationType> {
+    STATION_HINTS
+        .iter()
+        .find(|(hint, _)| name.contains(hint))
+        .map(|(_, station)| *station)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn configured(pattern: &str) -> AgentTypeConfig {
+        AgentTypeConfig {
+            pattern: pattern.to_string(),
+            label: None,
+            color: None,
+            home_station: None,
+            sprite: None,
+        }
+    }
+
+    #[test]
+    fn built_in_types_keep_their_look() {
+        let explore = AgentTypes::default().resolve("Explore");
+        assert_eq!(explore.id(), "explore");
+        assert_eq!(explore.label(), "Explore");
+        assert_eq!(explore.home_station(), StationType::Library);
+    }
+
+    #[test]
+    fn configured_types_come_first_and_fill_in_what_they_leave_out() {
+        let types = AgentTypes::new(&[
+            AgentTypeConfig {
+                label: Some("Reviewer".to_string()),
+                home_station: Some(StationType::Desk),
+                sprite: Some("Levi".to_string()),
+                ..configured("*-Reviewer")
+            },
+            configured("db-migrator"),
+        ]);
+
+        let reviewer = types.resolve("code-reviewer");
+        assert_eq!(reviewer.label(), "Reviewer");
+        assert_eq!(reviewer.home_station(), StationType::Desk);
+        assert_eq!(reviewer.sprite(), Some("Levi"));
+        // Every match of a pattern is the same type
+        assert_eq!(types.resolve("api-reviewer"), reviewer);
+
+        let migrator = types.resolve("db-migrator");
+        assert_eq!(migrator.label(), "DMigrator");
+        assert_eq!(migrator.home_station(), StationType::Terminal);
+    }
+
+    #[test]
+    fn unknown_types_get_a_stable_identity_of_their_own() {
+        let types = AgentTypes::default();
+        let first = types.resolve("release-captain");
+        let second = types.resolve("incident-scribe");
+
+        assert_eq!(first, AgentTypes::default().resolve("Release-Captain"));
+        assert_eq!(first.label(), "RCaptain");
+        assert_ne!(first.color(), second.color());
+        assert_ne!(first.color(), types.resolve("general-purpose").color());
+    }
+
+    #[test]
+    fn patterns_match_whole_names() {
+        assert!(matches("*-reviewer", "code-reviewer"));
+        assert!(matches("db-*-bot", "db-backup-bot"));
+        assert!(!matches("*-reviewer", "code-reviewer-2"));
+        assert!(!matches("plan", "planner"));
+        assert!(matches("*", "anything"));
+    }
+}
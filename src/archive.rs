@@ -0,0 +1,162 @@
+use chrono::{DateTime, Utc};
+use flate2::read::MultiGzDecoder;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use crate::events::{EventParser, ToolEvent};
+
+/// First bytes of a gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// First bytes of a zstd frame
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// File name endings of event logs, plain or archived
+const LOG_EXTENSIONS: [&str; 3] = [".jsonl", ".jsonl.gz", ".jsonl.zst"];
+
+/// Open a log for reading, decompressing it if it is gzip or zstd.
+///
+/// The format is told by the first bytes rather than the file name, so a
+/// compressed log renamed to `.jsonl` still reads correctly.
+pub fn open_log(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let header = reader.fill_buf()?;
+
+    if header.starts_with(&GZIP_MAGIC) {
+        // Archives made by appending several gzip files are common with `gzip -c >>`
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else if header.starts_with(&ZSTD_MAGIC) {
+        Ok(Box::new(BufReader::new(zstd::Decoder::with_buffer(reader)?)))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Read a whole log, decompressing it if needed.
+///
+/// Returns bytes rather than text, since one bad byte or a line still being
+/// written should not make the rest unreadable.
+pub fn read_log(path: &Path) -> io::Result<Vec<u8>> {
+    let mut contents = Vec::new();
+    open_log(path)?.read_to_end(&mut contents)?;
+    Ok(contents)
+}
+
+/// When a log was last written, for events recorded without a time
+pub fn modified(path: &Path) -> DateTime<Utc> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map_or(DateTime::UNIX_EPOCH, DateTime::from)
+}
+
+/// The logs to read for `path`: the file itself, or every event log in a directory
+pub fn log_files(path: &Path) -> io::Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(path)? {
+        let path = entry?.path();
+        if path.is_file() && is_log_file(&path) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn is_log_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    LOG_EXTENSIONS.iter().any(|extension| name.ends_with(extension))
+}
+
+/// Something wrong with a recording that did not stop the rest being read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadProblem {
+    /// A line that could not be parsed
+    Dropped {
+        source: String,
+        line_number: u64,
+        message: String,
+    },
+    /// A log that could not be opened or broke off partway, such as an archive cut short
+    IoError { source: String, message: String },
+}
+
+/// Every event read from a recording, for replay and export alike
+#[derive(Debug, Default)]
+pub struct Recording {
+    /// Logs in the order they were recorded, each in file order
+    pub events: Vec<ToolEvent>,
+    /// Non-blank lines read, parsed or not
+    pub lines_read: usize,
+    pub problems: Vec<LoadProblem>,
+}
+
+/// Read a recorded log, or every log in a directory.
+///
+/// A log that breaks off keeps the events read before the break, and in a
+/// directory one that cannot be opened is skipped; both are noted in
+/// `problems`. Only a single log that cannot be opened at all is an error.
+pub fn load_recording(path: &Path) -> io::Result<Recording> {
+    let is_dir = path.is_dir();
+    let mut recording = Recording::default();
+    let mut logs = Vec::new();
+
+    for file in log_files(path)? {
+        match read_events(&file, &mut recording) {
+            Ok(events) => logs.push(events),
+            Err(err) if is_dir => recording.problems.push(LoadProblem::IoError {
+                source: file.display().to_string(),
+                message: err.to_string(),
+            }),
+            Err(err) => return Err(err),
+        }
+    }
+
+    // Archived sessions play back in the order they were recorded
+    logs.sort_by_key(|events| events.first().map(|event| event.timestamp));
+    recording.events = logs.into_iter().flatten().collect();
+    Ok(recording)
+}
+
+/// Parse every line of a plain or compressed log, noting lines read and problems in `recording`
+fn read_events(path: &Path, recording: &mut Recording) -> io::Result<Vec<ToolEvent>> {
+    let source = path.display().to_string();
+    let mut events = Vec::new();
+    let mut parser = EventParser::recorded(modified(path));
+
+    for (index, line) in open_log(path)?.split(b'\n').enumerate() {
+        let line_number = index as u64 + 1;
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => {
+                recording.problems.push(LoadProblem::IoError {
+                    source,
+                    message: format!("stopped at line {}: {}", line_number, err),
+                });
+                break;
+            }
+        };
+        let line = String::from_utf8_lossy(&line);
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        recording.lines_read += 1;
+        match parser.parse(line, line_number) {
+            Ok(event) => events.push(event),
+            Err(err) => recording.problems.push(LoadProblem::Dropped {
+                source: source.clone(),
+                line_number,
+                message: err.to_string(),
+            }),
+        }
+    }
+
+    Ok(events)
+}
@@ -0,0 +1,164 @@
+use clap::{Args, Parser, Subcommand};
+use std::path::{Path, PathBuf};
+
+use agentic_visualiser::hooks::{InstallOptions, SettingsScope};
+use agentic_visualiser::resources::DEFAULT_DEDUP_WINDOW;
+use agentic_visualiser::sources::{SourceSpec, EVENTS_PATH_ENV};
+use agentic_visualiser::systems::replay::SPEED_STEPS;
+
+/// A Gather Town-style 2D visualizer for Claude Code's agentic behavior
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// What to do; watches the events file when left out
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Follow live events as Claude Code works
+    Watch(WatchArgs),
+    /// Play back a recorded events file, or a directory of archived logs
+    Replay(ReplayArgs),
+    /// Show a generated session, without needing Claude Code running
+    Demo(DemoArgs),
+    /// Convert recorded logs into the visualiser's own event format
+    Export(ExportArgs),
+    /// Add the hooks that write the events file to Claude Code's settings
+    InstallHooks(InstallHooksArgs),
+    /// Append a hook payload from stdin to the events file; run by Claude Code
+    #[command(hide = true)]
+    Hook(HookArgs),
+}
+
+impl Default for Command {
+    fn default() -> Self {
+        Command::Watch(WatchArgs::default())
+    }
+}
+
+#[derive(Debug, Default, Args)]
+pub struct WatchArgs {
+    /// Events file to tail, instead of ~/.claude-visualizer/events.jsonl
+    #[arg(long, value_name = "PATH", env = EVENTS_PATH_ENV)]
+    pub events: Option<PathBuf>,
+
+    /// Extra event source: file:PATH, stdin, unix:PATH, tcp:PORT or tcp:HOST:PORT (loopback only)
+    #[arg(long = "source", value_name = "SPEC")]
+    pub sources: Vec<SourceSpec>,
+
+    /// How many recent events to remember when dropping duplicates
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_DEDUP_WINDOW)]
+    pub dedup_window: usize,
+}
+
+impl WatchArgs {
+    /// The sources to read: the events file unless only other sources were
+    /// given, at `default_events` unless `--events` says otherwise
+    pub fn source_specs(&self, default_events: &Path) -> Vec<SourceSpec> {
+        let mut specs = Vec::new();
+        match &self.events {
+            Some(path) => specs.push(SourceSpec::File(path.clone())),
+            None if self.sources.is_empty() => specs.push(SourceSpec::File(default_events.to_path_buf())),
+            None => {}
+        }
+        specs.extend(self.sources.iter().cloned());
+        specs
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ReplayArgs {
+    /// Recorded events file (.jsonl, .jsonl.gz or .jsonl.zst) or a directory of them
+    pub file: PathBuf,
+
+    /// Playback speed multiplier, from 0.5 to 20
+    #[arg(long, value_name = "N", default_value_t = 1.0, value_parser = parse_speed)]
+    pub speed: f32,
+}
+
+/// A playback speed within the range the speed keys step through
+fn parse_speed(s: &str) -> Result<f32, String> {
+    let speed: f32 = s.parse().map_err(|_| format!("'{}' is not a number", s))?;
+    let (slowest, fastest) = (SPEED_STEPS[0], SPEED_STEPS[SPEED_STEPS.len() - 1]);
+    if !(slowest..=fastest).contains(&speed) {
+        return Err(format!("speed must be between {} and {}", slowest, fastest));
+    }
+    Ok(speed)
+}
+
+#[derive(Debug, Args)]
+pub struct DemoArgs {
+    /// Seed for the generator; the same seed plays the same session
+    #[arg(long, value_name = "N")]
+    pub seed: Option<u64>,
+
+    /// Playback speed multiplier, from 0.5 to 20
+    #[arg(long, value_name = "N", default_value_t = 1.0, value_parser = parse_speed)]
+    pub speed: f32,
+
+    /// Write generated events to this file instead of showing them, for replay later
+    #[arg(long, short, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// How many events to write with --output
+    #[arg(long, value_name = "N", default_value_t = 500, requires = "output")]
+    pub count: usize,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// Recorded events file or directory of archived logs
+    pub input: PathBuf,
+
+    /// Where to write the events; standard output if left out
+    #[arg(long, short, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct InstallHooksArgs {
+    /// Change .claude/settings.json in the current project instead of the user settings
+    #[arg(long, conflicts_with = "settings")]
+    pub project: bool,
+
+    /// Settings file to change
+    #[arg(long, value_name = "PATH")]
+    pub settings: Option<PathBuf>,
+
+    /// Events file the hooks should write to, instead of the default
+    #[arg(long, value_name = "PATH")]
+    pub events: Option<PathBuf>,
+
+    /// Show what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Remove the visualiser's hooks again
+    #[arg(long, conflicts_with = "events")]
+    pub uninstall: bool,
+}
+
+impl InstallHooksArgs {
+    pub fn options(&self) -> InstallOptions {
+        let scope = match (&self.settings, self.project) {
+            (Some(path), _) => SettingsScope::File(path.clone()),
+            (None, true) => SettingsScope::Project,
+            (None, false) => SettingsScope::User,
+        };
+        InstallOptions {
+            scope,
+            events: self.events.clone(),
+            dry_run: self.dry_run,
+            uninstall: self.uninstall,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct HookArgs {
+    /// Events file to append to
+    #[arg(long, value_name = "PATH", env = EVENTS_PATH_ENV)]
+    pub events: Option<PathBuf>,
+}
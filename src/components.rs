@@ -1,98 +1,7 @@
 use bevy::prelude::*;
+use serde::Deserialize;
 
-/// Types of agents that can exist in the visualization
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
-pub enum AgentType {
-    #[default]
-    Main,
-    Explore,
-    Plan,
-    Bash,
-    CodeReviewer,
-    UIUXReviewer,
-    StatuslineSetup,
-    ClaudeCodeGuide,
-    Haiku,
-    DevopsEngineer,
-    SecurityAnalyst,
-    ProjectManager,
-    General,
-}
-
-impl AgentType {
-    pub fn from_str(s: &str) -> Self {
-        match s.to_lowercase().as_str() {
-            "explore" => AgentType::Explore,
-            "plan" => AgentType::Plan,
-            "bash" => AgentType::Bash,
-            "code-reviewer" => AgentType::CodeReviewer,
-            "ui-ux-reviewer" => AgentType::UIUXReviewer,
-            "statusline-setup" => AgentType::StatuslineSetup,
-            "claude-code-guide" => AgentType::ClaudeCodeGuide,
-            "haiku" => AgentType::Haiku,
-            "devops-engineer" => AgentType::DevopsEngineer,
-            "security-analyst" => AgentType::SecurityAnalyst,
-            "project-manager" => AgentType::ProjectManager,
-            "general-purpose" => AgentType::General,
-            _ => AgentType::General,
-        }
-    }
-
-    pub fn color(&self) -> Color {
-        match self {
-            AgentType::Main => Color::srgb(0.2, 0.6, 1.0),      // Blue
-            AgentType::Explore => Color::srgb(0.2, 0.8, 0.4),   // Green
-            AgentType::Plan => Color::srgb(0.7, 0.3, 0.9),      // Purple
-            AgentType::Bash => Color::srgb(1.0, 0.6, 0.2),      // Orange
-            AgentType::CodeReviewer => Color::srgb(1.0, 0.3, 0.3), // Red
-            AgentType::UIUXReviewer => Color::srgb(1.0, 0.8, 0.2), // Yellow
-            AgentType::StatuslineSetup => Color::srgb(0.8, 0.2, 0.2), // Dark red (Edward's coat)
-            AgentType::ClaudeCodeGuide => Color::srgb(0.4, 0.8, 0.4), // Green (Deku)
-            AgentType::Haiku => Color::srgb(0.6, 0.6, 0.6),     // Gray (Totoro)
-            AgentType::DevopsEngineer => Color::srgb(0.3, 0.8, 0.9), // Cyan (Bulma)
-            AgentType::SecurityAnalyst => Color::srgb(0.2, 0.3, 0.6), // Dark blue (Conan)
-            AgentType::ProjectManager => Color::srgb(0.4, 0.5, 0.3), // Military green (Erwin)
-            AgentType::General => Color::srgb(0.9, 0.8, 0.3),   // Yellow (Pikachu)
-        }
-    }
-
-    pub fn label(&self) -> &'static str {
-        match self {
-            AgentType::Main => "Main",
-            AgentType::Explore => "Explore",
-            AgentType::Plan => "Plan",
-            AgentType::Bash => "Bash",
-            AgentType::CodeReviewer => "Review",
-            AgentType::UIUXReviewer => "UI/UX",
-            AgentType::StatuslineSetup => "Setup",
-            AgentType::ClaudeCodeGuide => "Guide",
-            AgentType::Haiku => "Haiku",
-            AgentType::DevopsEngineer => "DevOps",
-            AgentType::SecurityAnalyst => "Security",
-            AgentType::ProjectManager => "PM",
-            AgentType::General => "Agent",
-        }
-    }
-
-    /// Get the home station for this agent type (where they do their primary work)
-    pub fn home_station(&self) -> StationType {
-        match self {
-            AgentType::Main => StationType::Center,
-            AgentType::Explore => StationType::Library,        // Explores files
-            AgentType::Plan => StationType::Desk,              // Plans/writes
-            AgentType::Bash => StationType::Terminal,          // Runs commands
-            AgentType::CodeReviewer => StationType::Library,   // Reviews code (reads)
-            AgentType::UIUXReviewer => StationType::Desk,      // Reviews UI (design)
-            AgentType::StatuslineSetup => StationType::Terminal,
-            AgentType::ClaudeCodeGuide => StationType::Library,
-            AgentType::Haiku => StationType::Center,           // Fast, stays central
-            AgentType::DevopsEngineer => StationType::Terminal, // DevOps = terminal work
-            AgentType::SecurityAnalyst => StationType::Library, // Security = code review
-            AgentType::ProjectManager => StationType::MeetingArea, // Coordinates
-            AgentType::General => StationType::Center,
-        }
-    }
-}
+pub use crate::agent_types::AgentType;
 
 /// Agent component - identifies an entity as an agent
 #[derive(Component)]
@@ -102,6 +11,17 @@ pub struct Agent {
     pub tool_use_id: Option<String>,
 }
 
+/// The agent whose Task started this subagent
+#[derive(Component)]
+pub struct SpawnedBy(pub Entity);
+
+/// Subagent still running when the subagent that started it finished;
+/// fades out where it stands, then is despawned
+#[derive(Component)]
+pub struct Orphaned {
+    pub timer: Timer,
+}
+
 /// Tracks which station an agent is currently at
 #[derive(Component, Default)]
 pub struct CurrentStation {
@@ -109,7 +29,8 @@ pub struct CurrentStation {
 }
 
 /// Types of workstations in the workspace
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum StationType {
     Library,    // Read, Grep, Glob
     Desk,       // Write, Edit
@@ -171,6 +92,11 @@ pub struct MovementTarget {
 #[derive(Component)]
 pub struct Speed(pub f32);
 
+impl Speed {
+    /// Subagents hurry a little more than the main agent
+    pub const SUBAGENT: Speed = Speed(180.0);
+}
+
 impl Default for Speed {
     fn default() -> Self {
         Speed(150.0) // pixels per second
@@ -245,3 +171,90 @@ pub struct SessionText;
 /// Agent count display
 #[derive(Component)]
 pub struct AgentCountText;
+
+/// "Log rotated" marker in the status bar
+#[derive(Component)]
+pub struct LogRotatedText;
+
+/// Replay speed and progress display
+#[derive(Component)]
+pub struct ReplayStatusText;
+
+/// Demo mode indicator
+#[derive(Component)]
+pub struct DemoStatusText;
+
+/// Clickable track of the timeline bar
+#[derive(Component)]
+pub struct TimelineTrack;
+
+/// Filled part of the timeline bar, up to the current position
+#[derive(Component)]
+pub struct TimelineFill;
+
+/// Timeline position display
+#[derive(Component)]
+pub struct TimelineText;
+
+/// Ingestion diagnostics overlay, toggled with F3
+#[derive(Component)]
+pub struct DiagnosticsPanel;
+
+/// Counters and recent failures inside the diagnostics overlay
+#[derive(Component)]
+pub struct DiagnosticsText;
+
+/// Hint shown while the overlay is closed and something has failed
+#[derive(Component)]
+pub struct DiagnosticsHint;
+
+/// Sidebar listing every session seen
+#[derive(Component)]
+pub struct SessionPanel;
+
+/// One session in the sidebar; click to pin it
+#[derive(Component)]
+pub struct SessionRow {
+    pub session_id: String,
+}
+
+/// Last line of the sidebar, counting older closed sessions; click to list them
+#[derive(Component)]
+pub struct CollapsedSessionsRow;
+
+/// Main agent sitting down at the end of a turn, waiting for the user
+#[derive(Component)]
+pub struct Resting;
+
+/// Main agent squashed while the conversation is being compacted
+#[derive(Component)]
+pub struct Compacting {
+    pub timer: Timer,
+}
+
+/// What a speech bubble above the main agent is saying
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BubbleKind {
+    /// A new prompt from the user
+    Task,
+    /// A permission request or other notification
+    Alert,
+    /// Context compaction in progress
+    Compaction,
+}
+
+/// Speech bubble shown above the main agent
+#[derive(Component)]
+pub struct SpeechBubble {
+    pub kind: BubbleKind,
+    /// Bubbles without a timer stay until replaced or cleared
+    pub timer: Option<Timer>,
+}
+
+/// Workspace floor, lit while a session is open
+#[derive(Component)]
+pub struct OfficeFloor;
+
+/// Alpha an entity had before its room started fading out
+#[derive(Component)]
+pub struct FadeBase(pub f32);
@@ -0,0 +1,307 @@
+//! Settings read from `~/.claude-visualizer/config.toml`.
+//!
+//! Every key is optional; anything left out keeps its built-in default. The
+//! file is watched while the visualiser runs, see `ConfigPlugin`.
+//!
+//! ```toml
+//! [window]
+//! width = 1200
+//! height = 800
+//! clear_color = [0.08, 0.08, 0.12]
+//!
+//! [agents]
+//! main_speed = 150.0
+//! subagent_speed = 180.0
+//! orbit_radius = 45.0
+//!
+//! # Custom subagents, matched on `subagent_type`; `*` matches anything.
+//! # Every key but `pattern` is optional and worked out from the name if left out.
+//! [[agents.types]]
+//! pattern = "*-reviewer"
+//! label = "Review"
+//! color = [1.0, 0.3, 0.3]
+//! home_station = "library"   # desk, terminal, web_portal, meeting_area or center
+//! sprite = "Levi"
+//!
+//! [events]
+//! path = "/tmp/work-profile/events.jsonl"
+//! per_frame = 3
+//! history_limit = 20000
+//!
+//! [sessions]
+//! idle_timeout = 300.0
+//! ```
+
+use anyhow::Context;
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::components::{Speed, StationType};
+use crate::resources::{DEFAULT_EVENTS_PER_FRAME, DEFAULT_HISTORY_LIMIT, DEFAULT_ORBIT_RADIUS};
+use crate::sprites::character_names;
+
+/// Environment variable that overrides where the config file is
+pub const CONFIG_PATH_ENV: &str = "CLAUDE_VISUALIZER_CONFIG";
+
+/// Location of the config file, unless overridden by `CLAUDE_VISUALIZER_CONFIG`
+pub fn config_path() -> PathBuf {
+    if let Some(path) = std::env::var_os(CONFIG_PATH_ENV).filter(|path| !path.is_empty()) {
+        return PathBuf::from(path);
+    }
+
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".claude-visualizer")
+        .join("config.toml")
+}
+
+/// Everything the config file can set
+#[derive(Resource, Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub window: WindowConfig,
+    pub agents: AgentConfig,
+    pub events: EventsConfig,
+    pub sessions: SessionsConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowConfig {
+    pub width: f32,
+    pub height: f32,
+    /// Background as `[red, green, blue]` from 0 to 1
+    pub clear_color: [f32; 3],
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            width: 900.0,
+            height: 700.0,
+            clear_color: [0.08, 0.08, 0.12],
+        }
+    }
+}
+
+impl WindowConfig {
+    pub fn clear_color(&self) -> Color {
+        let [red, green, blue] = self.clear_color;
+        Color::srgb(red, green, blue)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AgentConfig {
+    /// Pixels per second
+    pub main_speed: f32,
+    /// Pixels per second
+    pub subagent_speed: f32,
+    /// Distance from the station centre when agents share a station
+    pub orbit_radius: f32,
+    /// Subagent types, checked in order before the built-in ones
+    pub types: Vec<AgentTypeConfig>,
+}
+
+/// One `[[agents.types]]` entry
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AgentTypeConfig {
+    /// `subagent_type` to match, ignoring case; `*` matches any run of characters
+    pub pattern: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// `[red, green, blue]` from 0 to 1
+    #[serde(default)]
+    pub color: Option<[f32; 3]>,
+    #[serde(default)]
+    pub home_station: Option<StationType>,
+    /// Character to draw, such as "Levi"
+    #[serde(default)]
+    pub sprite: Option<String>,
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        AgentConfig {
+            main_speed: Speed::default().0,
+            subagent_speed: Speed::SUBAGENT.0,
+            orbit_radius: DEFAULT_ORBIT_RADIUS,
+            types: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EventsConfig {
+    /// Events file to tail; `CLAUDE_VISUALIZER_EVENTS` and `--events` take precedence
+    pub path: Option<PathBuf>,
+    /// Most events played per frame
+    pub per_frame: usize,
+    /// Most events kept for seeking back through
+    pub history_limit: usize,
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        EventsConfig {
+            path: None,
+            per_frame: DEFAULT_EVENTS_PER_FRAME,
+            history_limit: DEFAULT_HISTORY_LIMIT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionsConfig {
+    /// Seconds without events before a session's room fades out
+    pub idle_timeout: f32,
+}
+
+impl Default for SessionsConfig {
+    fn default() -> Self {
+        SessionsConfig { idle_timeout: 300.0 }
+    }
+}
+
+impl Config {
+    /// Read the config at `path`, or the defaults if there is no file
+    pub fn load(path: &Path) -> anyhow::Result<Config> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(e).with_context(|| format!("could not read {}", path.display())),
+        };
+
+        toml::from_str(&text).with_context(|| format!("invalid config in {}", path.display()))
+    }
+
+    /// Replace settings that cannot work, such as a speed of 0 or a character
+    /// that does not exist, returning a message for each one replaced
+    pub fn fix_invalid(&mut self) -> Vec<String> {
+        let defaults = Config::default();
+        let mut problems = Vec::new();
+
+        let window = &mut self.window;
+        positive(&mut problems, "window.width", &mut window.width, defaults.window.width);
+        positive(&mut problems, "window.height", &mut window.height, defaults.window.height);
+        within_unit(&mut problems, "window.clear_color", &mut window.clear_color);
+
+        let (agents, fallback) = (&mut self.agents, &defaults.agents);
+        positive(&mut problems, "agents.main_speed", &mut agents.main_speed, fallback.main_speed);
+        positive(&mut problems, "agents.subagent_speed", &mut agents.subagent_speed, fallback.subagent_speed);
+        positive(&mut problems, "agents.orbit_radius", &mut agents.orbit_radius, fallback.orbit_radius);
+        for entry in agents.types.iter_mut() {
+            if let Some(sprite) = &entry.sprite
+                && !character_names().any(|name| name.eq_ignore_ascii_case(sprite))
+            {
+                problems.push(format!(
+                    "no character called {} for agent type {}; choose from {}",
+                    sprite,
+                    entry.pattern,
+                    character_names().collect::<Vec<_>>().join(", ")
+                ));
+                entry.sprite = None;
+            }
+            if let Some(color) = entry.color.as_mut() {
+                within_unit(&mut problems, &format!("colour of agent type {}", entry.pattern), color);
+            }
+        }
+
+        let events = &mut self.events;
+        if events.per_frame == 0 {
+            problems.push(format!("events.per_frame must be above 0; using {}", DEFAULT_EVENTS_PER_FRAME));
+            events.per_frame = DEFAULT_EVENTS_PER_FRAME;
+        }
+        if events.history_limit == 0 {
+            problems.push(format!("events.history_limit must be above 0; using {}", DEFAULT_HISTORY_LIMIT));
+            events.history_limit = DEFAULT_HISTORY_LIMIT;
+        }
+
+        let idle_timeout = &mut self.sessions.idle_timeout;
+        positive(&mut problems, "sessions.idle_timeout", idle_timeout, defaults.sessions.idle_timeout);
+
+        problems
+    }
+}
+
+/// Put `default` in place of a `value` that is not above 0
+fn positive(problems: &mut Vec<String>, name: &str, value: &mut f32, default: f32) {
+    if *value > 0.0 && value.is_finite() {
+        return;
+    }
+    problems.push(format!("{} must be above 0, not {}; using {}", name, value, default));
+    *value = default;
+}
+
+/// Clamp every component of a colour to 0 to 1
+fn within_unit(problems: &mut Vec<String>, name: &str, color: &mut [f32; 3]) {
+    if color.iter().all(|component| (0.0..=1.0).contains(component)) {
+        return;
+    }
+    problems.push(format!("{} {:?} is outside 0 to 1; clamping it", name, color));
+    for component in color.iter_mut() {
+        *component = if component.is_nan() { 0.0 } else { component.clamp(0.0, 1.0) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_keys_keep_their_defaults() {
+        let config: Config = toml::from_str("[agents]\nsubagent_speed = 240.0\n").unwrap();
+        assert_eq!(config.agents.subagent_speed, 240.0);
+        assert_eq!(config.agents.main_speed, Speed::default().0);
+        assert_eq!(config.window, WindowConfig::default());
+        assert_eq!(config.events.per_frame, DEFAULT_EVENTS_PER_FRAME);
+    }
+
+    #[test]
+    fn agent_types_are_read_in_order() {
+        let config: Config = toml::from_str(
+            "[[agents.types]]\npattern = \"db-*\"\nhome_station = \"web_portal\"\n\n\
+             [[agents.types]]\npattern = \"*\"\nlabel = \"Crew\"\n",
+        )
+        .unwrap();
+        let types = &config.agents.types;
+        assert_eq!(types.len(), 2);
+        assert_eq!(types[0].home_station, Some(StationType::WebPortal));
+        assert_eq!(types[1].label.as_deref(), Some("Crew"));
+        assert_eq!(types[1].color, None);
+    }
+
+    #[test]
+    fn misspelt_keys_are_rejected() {
+        assert!(toml::from_str::<Config>("[agents]\norbit = 60.0\n").is_err());
+    }
+
+    #[test]
+    fn settings_that_cannot_work_are_replaced_by_their_defaults() {
+        let mut config: Config = toml::from_str(
+            "[window]\nwidth = 0\n\n[agents]\nmain_speed = -5.0\norbit_radius = 20.0\n\n\
+             [events]\nper_frame = 0\nhistory_limit = 0\n",
+        )
+        .unwrap();
+        assert_eq!(config.fix_invalid().len(), 4);
+
+        let defaults = Config::default();
+        assert_eq!(config.window.width, defaults.window.width);
+        assert_eq!(config.agents.main_speed, defaults.agents.main_speed);
+        assert_eq!(config.agents.orbit_radius, 20.0);
+        assert_eq!(config.events.per_frame, DEFAULT_EVENTS_PER_FRAME);
+        assert_eq!(config.events.history_limit, DEFAULT_HISTORY_LIMIT);
+        assert!(config.fix_invalid().is_empty());
+    }
+
+    #[test]
+    fn a_missing_file_is_the_defaults() {
+        let path = Path::new("/nonexistent/claude-visualizer/config.toml");
+        assert_eq!(Config::load(path).unwrap(), Config::default());
+    }
+}
@@ -0,0 +1,323 @@
+use chrono::{DateTime, Duration, Utc};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde_json::json;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::events::{EventType, ToolEvent};
+
+const DEMO_CWD: &str = "/home/demo/projects/pixel-office";
+
+const SOURCE_FILES: [&str; 8] = [
+    "src/main.rs",
+    "src/lib.rs",
+    "src/render/sprites.rs",
+    "src/render/camera.rs",
+    "src/net/client.rs",
+    "src/config.rs",
+    "tests/integration.rs",
+    "Cargo.toml",
+];
+
+const COMMANDS: [&str; 6] = [
+    "cargo build",
+    "cargo test --workspace",
+    "cargo clippy -- -D warnings",
+    "git status",
+    "git diff --stat",
+    "ls -la src",
+];
+
+const SEARCH_PATTERNS: [&str; 5] = ["fn spawn_", "TODO", "impl Plugin for", "unwrap\\(\\)", "struct .*Config"];
+
+const GLOB_PATTERNS: [&str; 3] = ["**/*.rs", "src/**/*.toml", "tests/*.rs"];
+
+const URLS: [&str; 4] = [
+    "https://docs.rs/bevy/latest/bevy/",
+    "https://doc.rust-lang.org/std/sync/mpsc/",
+    "https://github.com/bevyengine/bevy/issues",
+    "https://serde.rs/attributes.html",
+];
+
+const QUERIES: [&str; 3] = ["bevy 0.15 sprite atlas", "rust notify crate rename events", "serde flatten enum"];
+
+const SUBAGENT_TYPES: [&str; 6] = [
+    "Explore",
+    "Plan",
+    "general-purpose",
+    "code-reviewer",
+    "security-analyst",
+    "devops-engineer",
+];
+
+const PROMPTS: [&str; 4] = [
+    "Add a minimap to the office view",
+    "Why does the camera jitter when agents move?",
+    "Refactor the network client to reconnect on failure",
+    "Review the sprite loading code for panics",
+];
+
+/// Produces an endless, plausible Claude Code session for demos and bug reports.
+///
+/// The same seed always gives the same sequence of events and gaps between
+/// them, so a rendering bug seen in a demo can be reproduced exactly.
+pub struct DemoGenerator {
+    rng: StdRng,
+    session_id: String,
+    start: DateTime<Utc>,
+    /// Seconds after `start` at which the main agent does its next thing
+    next_action: f64,
+    /// Events already decided on, earliest first
+    pending: Vec<(f64, ToolEvent)>,
+    /// Tool calls left before the main agent ends its turn
+    turn_actions_left: u32,
+    tool_counter: u64,
+}
+
+impl DemoGenerator {
+    pub fn new(seed: u64, start: DateTime<Utc>) -> Self {
+        let mut generator = DemoGenerator {
+            rng: StdRng::seed_from_u64(seed),
+            session_id: format!("demo-{:016x}", seed),
+            start,
+            next_action: 0.0,
+            pending: Vec::new(),
+            turn_actions_left: 0,
+            tool_counter: 0,
+        };
+        generator.schedule(0.0, generator.event(EventType::SessionStart));
+        generator.next_action = 0.5;
+        generator
+    }
+
+    /// The next event, with the seconds since the start at which it happens
+    pub fn next_event(&mut self) -> (f64, ToolEvent) {
+        // Plan ahead until nothing still to be planned could come first
+        while self.pending.first().is_none_or(|(due, _)| *due > self.next_action) {
+            self.plan_action();
+        }
+        self.pending.remove(0)
+    }
+
+    /// Decide what the main agent does at `next_action`
+    fn plan_action(&mut self) {
+        let at = self.next_action;
+
+        if self.turn_actions_left == 0 {
+            self.start_turn(at);
+            return;
+        }
+        self.turn_actions_left -= 1;
+
+        if self.turn_actions_left == 0 {
+            self.end_turn(at);
+            return;
+        }
+
+        match self.rng.gen_range(0..100) {
+            0..=24 => self.main_tool(at, "Read"),
+            25..=39 => {
+                let tool = if self.rng.gen_bool(0.6) { "Grep" } else { "Glob" };
+                self.main_tool(at, tool)
+            }
+            40..=59 => {
+                let tool = if self.rng.gen_bool(0.8) { "Edit" } else { "Write" };
+                self.main_tool(at, tool)
+            }
+            60..=74 => self.main_tool(at, "Bash"),
+            75..=82 => {
+                let tool = if self.rng.gen_bool(0.5) { "WebFetch" } else { "WebSearch" };
+                self.main_tool(at, tool)
+            }
+            83..=96 => self.spawn_tasks(at),
+            _ => self.notify(at),
+        }
+    }
+
+    /// The user sends a prompt after a pause
+    fn start_turn(&mut self, at: f64) {
+        let mut event = self.event(EventType::UserPromptSubmit);
+        event.prompt = self.pick(&PROMPTS).to_string();
+        self.schedule(at, event);
+
+        self.turn_actions_left = self.rng.gen_range(6..14);
+        self.next_action = at + self.rng.gen_range(1.0..2.5);
+    }
+
+    /// The main agent finishes and waits for the user
+    fn end_turn(&mut self, at: f64) {
+        self.schedule(at, self.event(EventType::Stop));
+
+        // Long sessions get compacted now and then
+        if self.rng.gen_bool(0.15) {
+            self.schedule(at + 2.0, self.event(EventType::PreCompact));
+        }
+        self.next_action = at + self.rng.gen_range(4.0..8.0);
+    }
+
+    /// A permission prompt that the user answers after a moment
+    fn notify(&mut self, at: f64) {
+        let mut event = self.event(EventType::Notification);
+        event.message = "Claude needs your permission to use Bash".to_string();
+        self.schedule(at, event);
+        self.next_action = at + self.rng.gen_range(2.0..4.0);
+    }
+
+    /// A tool call by the main agent, which waits for it to finish
+    fn main_tool(&mut self, at: f64, tool_name: &str) {
+        let input = self.tool_input(tool_name);
+        let duration = match tool_name {
+            "Bash" => self.rng.gen_range(2.0..8.0),
+            "WebFetch" | "WebSearch" => self.rng.gen_range(2.0..5.0),
+            _ => self.rng.gen_range(0.8..2.5),
+        };
+
+        let (mut pre, post) = self.tool_call(tool_name, input);
+        pre.parent_tool_use_id = Some(String::new());
+        self.schedule(at, pre);
+        self.schedule(at + duration, post);
+        self.next_action = at + duration + self.rng.gen_range(0.3..1.2);
+    }
+
+    /// Tool calls made by the subagent of Task `task_id` while it runs,
+    /// now and then handing part of the work to a subagent of its own
+    fn subagent_tools(&mut self, task_id: &str, started: f64, finished: f64) {
+        let mut at = started + self.rng.gen_range(0.5..2.0);
+        loop {
+            if finished - at > 8.0 && self.rng.gen_bool(0.15) {
+                let delegated_until = at + self.rng.gen_range(4.0..(finished - at - 2.0).min(10.0));
+                self.task(task_id, at, delegated_until);
+                at = delegated_until + self.rng.gen_range(0.3..1.5);
+                continue;
+            }
+
+            let tool_name = self.pick(&["Read", "Read", "Grep", "Glob", "Bash", "WebFetch"]);
+            let duration = self.rng.gen_range(0.8..3.0);
+            if at + duration > finished - 0.5 {
+                break;
+            }
+
+            let input = self.tool_input(tool_name);
+            let (mut pre, post) = self.tool_call(tool_name, input);
+            pre.parent_tool_use_id = Some(task_id.to_string());
+            self.schedule(at, pre);
+            self.schedule(at + duration, post);
+            at += duration + self.rng.gen_range(0.3..1.5);
+        }
+    }
+
+    /// One to three subagents started together; the main agent waits for all of them
+    fn spawn_tasks(&mut self, at: f64) {
+        let count = self.rng.gen_range(1..=3);
+        let mut finished_by = at;
+
+        for index in 0..count {
+            let started = at + index as f64 * 0.2;
+            let finished = started + self.rng.gen_range(8.0..25.0);
+            finished_by = finished_by.max(finished);
+            self.task("", started, finished);
+        }
+
+        self.next_action = finished_by + self.rng.gen_range(0.5..1.5);
+    }
+
+    /// A Task from `parent_task`, empty for the main agent, and all its subagent does
+    fn task(&mut self, parent_task: &str, started: f64, finished: f64) {
+        let subagent_type = self.pick(&SUBAGENT_TYPES).to_string();
+        let prompt = format!("{} for: {}", subagent_type, self.pick(&PROMPTS));
+        let input = json!({
+            "subagent_type": subagent_type,
+            "description": format!("{} task", subagent_type),
+            "prompt": prompt,
+        });
+
+        let (mut pre, post) = self.tool_call("Task", input);
+        pre.subagent_type = subagent_type;
+        pre.subagent_prompt = prompt;
+        pre.parent_tool_use_id = Some(parent_task.to_string());
+        let task_id = pre.tool_use_id.clone();
+        self.schedule(started, pre);
+        self.subagent_tools(&task_id, started, finished);
+        self.schedule(finished - 0.05, self.event(EventType::SubagentStop));
+        self.schedule(finished, post);
+    }
+
+    /// A matching PreToolUse and PostToolUse pair
+    fn tool_call(&mut self, tool_name: &str, input: serde_json::Value) -> (ToolEvent, ToolEvent) {
+        self.tool_counter += 1;
+        let tool_use_id = format!("toolu_demo_{:06}", self.tool_counter);
+
+        let mut pre = self.event(EventType::PreToolUse);
+        pre.tool_name = tool_name.to_string();
+        pre.tool_use_id = tool_use_id;
+        pre.tool_input = Some(input);
+
+        let mut post = pre.clone();
+        post.event_type = EventType::PostToolUse;
+        post.tool_response = Some(json!({ "success": true }));
+
+        (pre, post)
+    }
+
+    fn tool_input(&mut self, tool_name: &str) -> serde_json::Value {
+        match tool_name {
+            "Read" | "Edit" | "Write" => {
+                json!({ "file_path": format!("{}/{}", DEMO_CWD, self.pick(&SOURCE_FILES)) })
+            }
+            "Bash" => json!({ "command": self.pick(&COMMANDS) }),
+            "Grep" => json!({ "pattern": self.pick(&SEARCH_PATTERNS) }),
+            "Glob" => json!({ "pattern": self.pick(&GLOB_PATTERNS) }),
+            "WebFetch" => json!({ "url": self.pick(&URLS) }),
+            "WebSearch" => json!({ "query": self.pick(&QUERIES) }),
+            _ => json!({}),
+        }
+    }
+
+    fn pick(&mut self, options: &[&'static str]) -> &'static str {
+        options.choose(&mut self.rng).copied().unwrap_or_default()
+    }
+
+    /// A bare event of this session; its time is filled in when scheduled
+    fn event(&self, event_type: EventType) -> ToolEvent {
+        ToolEvent {
+            timestamp: self.start,
+            session_id: self.session_id.clone(),
+            event_type,
+            tool_name: String::new(),
+            tool_use_id: String::new(),
+            cwd: DEMO_CWD.to_string(),
+            transcript_path: String::new(),
+            subagent_type: String::new(),
+            subagent_prompt: String::new(),
+            prompt: String::new(),
+            message: String::new(),
+            tool_input: None,
+            tool_response: None,
+            parent_tool_use_id: None,
+        }
+    }
+
+    /// Add an event at `at` seconds, after any already due at the same time
+    fn schedule(&mut self, at: f64, mut event: ToolEvent) {
+        event.timestamp = self.start + Duration::milliseconds((at * 1000.0) as i64);
+        let index = self.pending.partition_point(|(due, _)| *due <= at);
+        self.pending.insert(index, (at, event));
+    }
+}
+
+/// Write the first `count` events generated from `seed` as a JSONL recording
+pub fn write_events(seed: u64, count: usize, path: &Path) -> std::io::Result<()> {
+    let mut generator = DemoGenerator::new(seed, Utc::now());
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    for _ in 0..count {
+        let (_, event) = generator.next_event();
+        serde_json::to_writer(&mut writer, &event)?;
+        writer.write_all(b"\n")?;
+    }
+
+    writer.flush()
+}
@@ -0,0 +1,634 @@
+//! What is going on in the office, worked out from hook events alone.
+//!
+//! `State::apply` takes one event and reports what changed, without touching
+//! Bevy. The ECS systems turn those changes into sprites and movement; the
+//! timeline, exports and other front-ends can use the same state directly.
+//! `Sessions` keeps one `State` per session for when several run at once.
+
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+use crate::agent_types::{AgentType, AgentTypes};
+use crate::components::StationType;
+use crate::events::{EventType, ToolEvent};
+
+/// Who an agent is: the main agent, or a subagent named by the Task that started it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AgentId {
+    Main,
+    Subagent(String),
+}
+
+/// A subagent that is still working
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subagent {
+    /// Id of the Task tool call that started it
+    pub tool_use_id: String,
+    pub agent_type: AgentType,
+    pub station: StationType,
+    /// Agent whose Task started it
+    pub parent: AgentId,
+}
+
+/// Why a subagent stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinishReason {
+    /// Its Task returned
+    Completed,
+    /// The subagent that started it finished first, so nothing is waiting for it
+    ParentFinished,
+    /// Its session ended or was replaced
+    SessionOver,
+}
+
+/// A tool call that has started but not yet finished
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolCall {
+    pub tool_use_id: String,
+    pub tool_name: String,
+    pub agent: AgentId,
+    pub started: DateTime<Utc>,
+}
+
+/// Something that happened to the office as a result of an event
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// The first event of a session, or events now come from another one and
+    /// the old session's subagents were finished first
+    SessionSwitched { session_id: String },
+    SessionStarted,
+    /// The session is over; its subagents were finished first
+    SessionEnded,
+    SubagentSpawned {
+        tool_use_id: String,
+        agent_type: AgentType,
+        station: StationType,
+        parent: AgentId,
+    },
+    /// A subagent stopped; any it started have finished just before
+    SubagentFinished { tool_use_id: String, reason: FinishReason },
+    AgentMoved { agent: AgentId, station: StationType },
+    ToolStarted { call: ToolCall },
+    ToolFinished { tool_use_id: String, agent: AgentId },
+    /// The user submitted a prompt; carries a short summary of it
+    PromptSubmitted(String),
+    /// Claude finished its turn and is waiting for the user
+    TurnEnded,
+    /// Claude needs the user, usually to approve a tool; carries the message
+    AttentionNeeded(String),
+    Compacting,
+}
+
+/// Sessions, agents, tool calls and stations as far as the events so far tell
+#[derive(Debug, Clone)]
+pub struct State {
+    pub session_id: Option<String>,
+    /// Working directory of the session, from the latest event that had one
+    pub cwd: Option<String>,
+    /// Whether the office is lit; goes dark between SessionEnd and SessionStart
+    pub office_open: bool,
+    pub main_station: StationType,
+    /// Subagents still running, in spawn order
+    pub subagents: Vec<Subagent>,
+    /// Tool calls in progress, in start order
+    pub in_flight: Vec<ToolCall>,
+    /// Tool calls started in the current session
+    pub tool_calls: usize,
+    /// What is happening now and a short summary of it, for the status bar
+    pub activity: Option<(String, String)>,
+    /// What each `subagent_type` looks like and where it works
+    pub agent_types: Arc<AgentTypes>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State {
+            session_id: None,
+            cwd: None,
+            // Sessions already running when the visualiser starts never send SessionStart
+            office_open: true,
+            main_station: StationType::Center,
+            subagents: Vec::new(),
+            in_flight: Vec::new(),
+            tool_calls: 0,
+            activity: None,
+            agent_types: Arc::default(),
+        }
+    }
+}
+
+impl State {
+    /// The state after playing `events` from the start
+    pub fn replay<'a>(events: impl IntoIterator<Item = &'a ToolEvent>) -> Self {
+        let mut state = State::default();
+        for event in events {
+            state.apply(event);
+        }
+        state
+    }
+
+    /// Agents, the main agent included
+    pub fn agent_count(&self) -> usize {
+        1 + self.subagents.len()
+    }
+
+    pub fn subagent(&self, tool_use_id: &str) -> Option<&Subagent> {
+        self.subagents.iter().find(|s| s.tool_use_id == tool_use_id)
+    }
+
+    /// Subagents started by `parent`, in spawn order
+    pub fn children(&self, parent: &AgentId) -> impl Iterator<Item = &Subagent> {
+        self.subagents.iter().filter(move |s| &s.parent == parent)
+    }
+
+    /// Agents at `station`, the main agent first
+    pub fn agents_at(&self, station: StationType) -> Vec<AgentId> {
+        let main = (self.main_station == station).then_some(AgentId::Main);
+        let subagents = self
+            .subagents
+            .iter()
+            .filter(|s| s.station == station)
+            .map(|s| AgentId::Subagent(s.tool_use_id.clone()));
+        main.into_iter().chain(subagents).collect()
+    }
+
+    /// Update the state for one event, returning what changed in order
+    pub fn apply(&mut self, event: &ToolEvent) -> Vec<Change> {
+        let mut changes = Vec::new();
+
+        // A new session starts with only the main agent
+        if self.session_id.as_deref() != Some(event.session_id.as_str()) {
+            if self.session_id.is_some() {
+                self.finish_all(&mut changes);
+                self.tool_calls = 0;
+                self.cwd = None;
+            }
+            self.session_id = Some(event.session_id.clone());
+            changes.push(Change::SessionSwitched {
+                session_id: event.session_id.clone(),
+            });
+        }
+
+        self.activity = Some((event.activity_name(), event.input_summary()));
+        if !event.cwd.is_empty() {
+            self.cwd = Some(event.cwd.clone());
+        }
+
+        match event.event_type {
+            EventType::PreToolUse => self.start_tool(event, &mut changes),
+            EventType::PostToolUse => self.finish_tool(event, &mut changes),
+            EventType::SubagentStop => {
+                // The Task's PostToolUse follows and carries the id needed to finish it
+            }
+            EventType::UserPromptSubmit => {
+                changes.push(Change::PromptSubmitted(event.input_summary()));
+                self.move_main(StationType::Center, &mut changes);
+            }
+            EventType::Stop => {
+                changes.push(Change::TurnEnded);
+                self.move_main(StationType::Center, &mut changes);
+            }
+            EventType::Notification => changes.push(Change::AttentionNeeded(event.input_summary())),
+            EventType::SessionStart => {
+                self.office_open = true;
+                changes.push(Change::SessionStarted);
+            }
+            EventType::SessionEnd => {
+                // Everyone goes home when the office closes
+                self.finish_all(&mut changes);
+                self.office_open = false;
+                changes.push(Change::SessionEnded);
+            }
+            EventType::PreCompact => changes.push(Change::Compacting),
+            EventType::Unknown => {}
+        }
+
+        changes
+    }
+
+    fn start_tool(&mut self, event: &ToolEvent, changes: &mut Vec<Change>) {
+        // A hook that fired twice must not start the same call again
+        let repeated = !event.tool_use_id.is_empty()
+            && self.in_flight.iter().any(|call| call.tool_use_id == event.tool_use_id);
+        if repeated {
+            return;
+        }
+        self.tool_calls += 1;
+
+        let call = ToolCall {
+            tool_use_id: event.tool_use_id.clone(),
+            tool_name: event.tool_name.clone(),
+            agent: self.caller(event),
+            started: event.timestamp,
+        };
+        self.in_flight.push(call.clone());
+        changes.push(Change::ToolStarted { call: call.clone() });
+
+        if event.tool_name == "Task" {
+            // A Task starts a subagent at its home station
+            if let Some(subagent_type) = event.get_subagent_type() {
+                let agent_type = self.agent_types.resolve(&subagent_type);
+                let subagent = Subagent {
+                    tool_use_id: event.tool_use_id.clone(),
+                    station: agent_type.home_station(),
+                    agent_type,
+                    parent: call.agent.clone(),
+                };
+                changes.push(Change::SubagentSpawned {
+                    tool_use_id: subagent.tool_use_id.clone(),
+                    agent_type: subagent.agent_type.clone(),
+                    station: subagent.station,
+                    parent: subagent.parent.clone(),
+                });
+                self.subagents.push(subagent);
+            }
+        } else {
+            self.move_agent(call.agent, StationType::for_tool(&event.tool_name), changes);
+        }
+    }
+
+    /// Which agent made a tool call.
+    ///
+    /// The transcript says so when the call was found there. Otherwise, since
+    /// the main agent waits for its Tasks to finish, a call made while
+    /// subagents are running is taken to be theirs: the earliest one with no
+    /// call of its own in flight, or else the earliest.
+    fn caller(&self, event: &ToolEvent) -> AgentId {
+        match event.parent_tool_use_id.as_deref() {
+            Some("") => return AgentId::Main,
+            Some(task) if self.subagent(task).is_some() => return AgentId::Subagent(task.to_string()),
+            _ => {}
+        }
+        if event.tool_name == "Task" {
+            return AgentId::Main;
+        }
+
+        let busy = |subagent: &&Subagent| {
+            self.in_flight
+                .iter()
+                .any(|call| call.agent == AgentId::Subagent(subagent.tool_use_id.clone()))
+        };
+        self.subagents
+            .iter()
+            .find(|subagent| !busy(subagent))
+            .or(self.subagents.first())
+            .map_or(AgentId::Main, |subagent| AgentId::Subagent(subagent.tool_use_id.clone()))
+    }
+
+    fn finish_tool(&mut self, event: &ToolEvent, changes: &mut Vec<Change>) {
+        if let Some(index) = self
+            .in_flight
+            .iter()
+            .position(|call| call.tool_use_id == event.tool_use_id)
+        {
+            let call = self.in_flight.remove(index);
+            changes.push(Change::ToolFinished {
+                tool_use_id: call.tool_use_id,
+                agent: call.agent,
+            });
+        }
+
+        if event.tool_name == "Task" {
+            self.finish_subagent(&event.tool_use_id, FinishReason::Completed, changes);
+        }
+    }
+
+    /// Finish a subagent, and before it any it started that are still running
+    fn finish_subagent(&mut self, tool_use_id: &str, reason: FinishReason, changes: &mut Vec<Change>) {
+        if self.subagent(tool_use_id).is_none() {
+            return;
+        }
+
+        let agent = AgentId::Subagent(tool_use_id.to_string());
+        let children: Vec<String> = self.children(&agent).map(|s| s.tool_use_id.clone()).collect();
+        for child in children {
+            let reason = match reason {
+                FinishReason::SessionOver => FinishReason::SessionOver,
+                _ => FinishReason::ParentFinished,
+            };
+            self.finish_subagent(&child, reason, changes);
+        }
+
+        // Its calls will never report back
+        self.in_flight.retain(|call| call.agent != agent);
+        self.subagents.retain(|s| s.tool_use_id != tool_use_id);
+        changes.push(Change::SubagentFinished {
+            tool_use_id: tool_use_id.to_string(),
+            reason,
+        });
+    }
+
+    /// Finish every subagent and forget calls that will never complete
+    fn finish_all(&mut self, changes: &mut Vec<Change>) {
+        let top_level: Vec<String> = self.children(&AgentId::Main).map(|s| s.tool_use_id.clone()).collect();
+        for tool_use_id in top_level {
+            self.finish_subagent(&tool_use_id, FinishReason::SessionOver, changes);
+        }
+        // Started by a subagent that is somehow gone already
+        for subagent in std::mem::take(&mut self.subagents) {
+            changes.push(Change::SubagentFinished {
+                tool_use_id: subagent.tool_use_id,
+                reason: FinishReason::SessionOver,
+            });
+        }
+        self.in_flight.clear();
+    }
+
+    fn move_main(&mut self, station: StationType, changes: &mut Vec<Change>) {
+        self.move_agent(AgentId::Main, station, changes);
+    }
+
+    fn move_agent(&mut self, agent: AgentId, station: StationType, changes: &mut Vec<Change>) {
+        match &agent {
+            AgentId::Main => self.main_station = station,
+            AgentId::Subagent(tool_use_id) => {
+                if let Some(subagent) = self.subagents.iter_mut().find(|s| &s.tool_use_id == tool_use_id) {
+                    subagent.station = station;
+                }
+            }
+        }
+        changes.push(Change::AgentMoved { agent, station });
+    }
+}
+
+/// Every session still shown, each with its own state, in the order they were first seen
+#[derive(Debug, Clone, Default)]
+pub struct Sessions {
+    pub sessions: Vec<State>,
+    /// Registry handed to every session
+    pub agent_types: Arc<AgentTypes>,
+}
+
+impl Sessions {
+    /// The sessions after playing `events` from the start, with `agent_types`
+    pub fn replay<'a>(agent_types: Arc<AgentTypes>, events: impl IntoIterator<Item = &'a ToolEvent>) -> Self {
+        let mut sessions = Sessions {
+            agent_types,
+            ..Sessions::default()
+        };
+        for event in events {
+            sessions.apply(event);
+        }
+        sessions
+    }
+
+    pub fn get(&self, session_id: &str) -> Option<&State> {
+        self.sessions
+            .iter()
+            .find(|state| state.session_id.as_deref() == Some(session_id))
+    }
+
+    /// Agents across all sessions, each main agent included
+    pub fn agent_count(&self) -> usize {
+        self.sessions.iter().map(State::agent_count).sum()
+    }
+
+    /// Update the state of the event's session, starting one if it is new
+    pub fn apply(&mut self, event: &ToolEvent) -> Vec<Change> {
+        let index = self
+            .sessions
+            .iter()
+            .position(|state| state.session_id.as_deref() == Some(event.session_id.as_str()))
+            .unwrap_or_else(|| {
+                self.sessions.push(State {
+                    agent_types: self.agent_types.clone(),
+                    ..State::default()
+                });
+                self.sessions.len() - 1
+            });
+        self.sessions[index].apply(event)
+    }
+
+    /// Use `agent_types` for subagents started from now on
+    pub fn set_agent_types(&mut self, agent_types: Arc<AgentTypes>) {
+        for state in self.sessions.iter_mut() {
+            state.agent_types = agent_types.clone();
+        }
+        self.agent_types = agent_types;
+    }
+
+    /// Forget a session that is no longer shown, returning false if it was unknown
+    pub fn close(&mut self, session_id: &str) -> bool {
+        let before = self.sessions.len();
+        self.sessions
+            .retain(|state| state.session_id.as_deref() != Some(session_id));
+        self.sessions.len() < before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::{event, task, tool};
+
+    const SESSION: &str = "session-a";
+
+    #[test]
+    fn task_spawns_a_subagent_until_its_post_tool_use() {
+        let mut state = State::default();
+
+        let changes = state.apply(&task(SESSION, EventType::PreToolUse, "toolu_task", "Explore"));
+        assert!(changes.contains(&Change::SubagentSpawned {
+            tool_use_id: "toolu_task".to_string(),
+            agent_type: AgentTypes::default().resolve("Explore"),
+            station: StationType::Library,
+            parent: AgentId::Main,
+        }));
+        assert_eq!(state.agent_count(), 2);
+        assert_eq!(state.main_station, StationType::Center);
+        assert_eq!(state.agents_at(StationType::Library), vec![AgentId::Subagent("toolu_task".to_string())]);
+
+        let changes = state.apply(&task(SESSION, EventType::PostToolUse, "toolu_task", "Explore"));
+        assert_eq!(
+            changes,
+            vec![
+                Change::ToolFinished {
+                    tool_use_id: "toolu_task".to_string(),
+                    agent: AgentId::Main,
+                },
+                Change::SubagentFinished {
+                    tool_use_id: "toolu_task".to_string(),
+                    reason: FinishReason::Completed,
+                },
+            ]
+        );
+        assert_eq!(state.agent_count(), 1);
+        assert!(state.in_flight.is_empty());
+    }
+
+    #[test]
+    fn other_tools_move_the_main_agent() {
+        let mut state = State::default();
+
+        let changes = state.apply(&tool(SESSION, EventType::PreToolUse, "Read", "toolu_1"));
+        assert!(changes.contains(&Change::AgentMoved {
+            agent: AgentId::Main,
+            station: StationType::Library,
+        }));
+        assert_eq!(state.in_flight.len(), 1);
+        assert_eq!(state.activity.as_ref().map(|(name, _)| name.as_str()), Some("Read"));
+
+        state.apply(&event(SESSION, EventType::Stop));
+        assert_eq!(state.main_station, StationType::Center);
+    }
+
+    #[test]
+    fn a_repeated_pre_tool_use_is_ignored() {
+        let mut state = State::default();
+
+        state.apply(&task(SESSION, EventType::PreToolUse, "toolu_task", "Explore"));
+        let changes = state.apply(&task(SESSION, EventType::PreToolUse, "toolu_task", "Explore"));
+        assert_eq!(changes, vec![]);
+        assert_eq!(state.agent_count(), 2);
+        assert_eq!(state.tool_calls, 1);
+    }
+
+    #[test]
+    fn switching_session_finishes_the_old_subagents() {
+        let mut state = State::default();
+
+        state.apply(&task(SESSION, EventType::PreToolUse, "toolu_task", "Explore"));
+        let changes = state.apply(&event("session-b", EventType::UserPromptSubmit));
+        assert_eq!(
+            changes[..2],
+            [
+                Change::SubagentFinished {
+                    tool_use_id: "toolu_task".to_string(),
+                    reason: FinishReason::SessionOver,
+                },
+                Change::SessionSwitched { session_id: "session-b".to_string() },
+            ]
+        );
+        assert_eq!(state.agent_count(), 1);
+        assert_eq!(state.tool_calls, 0);
+        assert!(state.in_flight.is_empty());
+    }
+
+    #[test]
+    fn session_end_closes_the_office_until_the_next_start() {
+        let mut state = State::default();
+
+        state.apply(&task(SESSION, EventType::PreToolUse, "toolu_task", "Plan"));
+        let changes = state.apply(&event(SESSION, EventType::SessionEnd));
+        assert_eq!(changes.last(), Some(&Change::SessionEnded));
+        assert!(!state.office_open);
+        assert_eq!(state.agent_count(), 1);
+
+        state.apply(&event(SESSION, EventType::SessionStart));
+        assert!(state.office_open);
+    }
+
+    #[test]
+    fn replay_matches_applying_one_by_one() {
+        let events = [
+            tool(SESSION, EventType::PreToolUse, "Bash", "toolu_1"),
+            task(SESSION, EventType::PreToolUse, "toolu_task", "Explore"),
+            tool(SESSION, EventType::PostToolUse, "Bash", "toolu_1"),
+        ];
+
+        let state = State::replay(&events);
+        assert_eq!(state.main_station, StationType::for_tool("Bash"));
+        assert_eq!(state.subagents.len(), 1);
+        assert_eq!(state.in_flight.len(), 1);
+        assert_eq!(state.tool_calls, 2);
+    }
+
+    #[test]
+    fn concurrent_sessions_keep_their_own_subagents() {
+        let mut sessions = Sessions::default();
+
+        sessions.apply(&task(SESSION, EventType::PreToolUse, "toolu_a", "Explore"));
+        let changes = sessions.apply(&task("session-b", EventType::PreToolUse, "toolu_b", "Plan"));
+        assert_eq!(
+            changes[0],
+            Change::SessionSwitched {
+                session_id: "session-b".to_string()
+            }
+        );
+        assert!(!changes
+            .iter()
+            .any(|change| matches!(change, Change::SubagentFinished { .. })));
+        assert_eq!(sessions.agent_count(), 4);
+
+        sessions.apply(&task(SESSION, EventType::PostToolUse, "toolu_a", "Explore"));
+        assert_eq!(sessions.get(SESSION).unwrap().agent_count(), 1);
+        assert_eq!(sessions.get("session-b").unwrap().agent_count(), 2);
+
+        assert!(sessions.close("session-b"));
+        assert!(sessions.get("session-b").is_none());
+        assert_eq!(sessions.agent_count(), 1);
+    }
+
+    #[test]
+    fn tool_calls_go_to_the_subagent_that_made_them() {
+        let mut state = State::default();
+        state.apply(&task(SESSION, EventType::PreToolUse, "toolu_a", "Explore"));
+        state.apply(&task(SESSION, EventType::PreToolUse, "toolu_b", "Plan"));
+        let subagent = |id: &str| AgentId::Subagent(id.to_string());
+
+        // The transcript names the Task
+        let changes = state.apply(&ToolEvent {
+            parent_tool_use_id: Some("toolu_b".to_string()),
+            ..tool(SESSION, EventType::PreToolUse, "Bash", "toolu_1")
+        });
+        assert!(changes.contains(&Change::AgentMoved {
+            agent: subagent("toolu_b"),
+            station: StationType::for_tool("Bash"),
+        }));
+        assert_eq!(state.subagent("toolu_b").unwrap().station, StationType::for_tool("Bash"));
+        assert_eq!(state.main_station, StationType::Center);
+
+        // Without it, the first subagent not already busy
+        state.apply(&tool(SESSION, EventType::PreToolUse, "Read", "toolu_2"));
+        assert_eq!(state.in_flight.last().unwrap().agent, subagent("toolu_a"));
+
+        let changes = state.apply(&tool(SESSION, EventType::PostToolUse, "Read", "toolu_2"));
+        assert!(changes.contains(&Change::ToolFinished {
+            tool_use_id: "toolu_2".to_string(),
+            agent: subagent("toolu_a"),
+        }));
+
+        // The main agent can still be named explicitly
+        state.apply(&ToolEvent {
+            parent_tool_use_id: Some(String::new()),
+            ..tool(SESSION, EventType::PreToolUse, "Edit", "toolu_3")
+        });
+        assert_eq!(state.main_station, StationType::for_tool("Edit"));
+    }
+
+    #[test]
+    fn a_parent_finishing_first_takes_its_subagents_with_it() {
+        let mut state = State::default();
+        state.apply(&task(SESSION, EventType::PreToolUse, "toolu_parent", "general-purpose"));
+        let changes = state.apply(&ToolEvent {
+            parent_tool_use_id: Some("toolu_parent".to_string()),
+            ..task(SESSION, EventType::PreToolUse, "toolu_child", "Explore")
+        });
+        assert!(changes.contains(&Change::SubagentSpawned {
+            tool_use_id: "toolu_child".to_string(),
+            agent_type: AgentTypes::default().resolve("Explore"),
+            station: StationType::Library,
+            parent: AgentId::Subagent("toolu_parent".to_string()),
+        }));
+        state.apply(&tool(SESSION, EventType::PreToolUse, "Grep", "toolu_grep"));
+        assert_eq!(state.in_flight.last().unwrap().agent, AgentId::Subagent("toolu_child".to_string()));
+
+        let changes = state.apply(&task(SESSION, EventType::PostToolUse, "toolu_parent", "general-purpose"));
+        let finished: Vec<_> = changes
+            .iter()
+            .filter_map(|change| match change {
+                Change::SubagentFinished { tool_use_id, reason } => Some((tool_use_id.as_str(), *reason)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            finished,
+            vec![
+                ("toolu_child", FinishReason::ParentFinished),
+                ("toolu_parent", FinishReason::Completed),
+            ]
+        );
+        assert_eq!(state.agent_count(), 1);
+        assert!(state.in_flight.is_empty());
+    }
+}
@@ -1,5 +1,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
 
 /// Event types from Claude Code hooks
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -7,17 +10,43 @@ pub enum EventType {
     PreToolUse,
     PostToolUse,
     SubagentStop,
+    UserPromptSubmit,
+    Stop,
+    Notification,
+    SessionStart,
+    SessionEnd,
+    PreCompact,
     #[serde(other)]
     Unknown,
 }
 
+impl EventType {
+    /// Status bar label for events that are not about a tool call
+    pub fn label(&self) -> Option<&'static str> {
+        match self {
+            EventType::UserPromptSubmit => Some("Thinking"),
+            EventType::Stop => Some("Waiting for user"),
+            EventType::Notification => Some("Needs attention"),
+            EventType::SessionStart => Some("Session started"),
+            EventType::SessionEnd => Some("Session ended"),
+            EventType::PreCompact => Some("Compacting"),
+            EventType::PreToolUse
+            | EventType::PostToolUse
+            | EventType::SubagentStop
+            | EventType::Unknown => None,
+        }
+    }
+}
+
 /// A tool event captured from Claude Code hooks
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolEvent {
     pub timestamp: DateTime<Utc>,
     pub session_id: String,
     pub event_type: EventType,
+    #[serde(default)]
     pub tool_name: String,
+    #[serde(default)]
     pub tool_use_id: String,
     #[serde(default)]
     pub cwd: String,
@@ -27,13 +56,37 @@ pub struct ToolEvent {
     pub subagent_type: String,
     #[serde(default)]
     pub subagent_prompt: String,
+    /// What the user typed, for UserPromptSubmit
+    #[serde(default)]
+    pub prompt: String,
+    /// Notification text, such as a permission request
+    #[serde(default)]
+    pub message: String,
     pub tool_input: Option<serde_json::Value>,
     pub tool_response: Option<serde_json::Value>,
+    /// Task call this tool call was made inside, as far as the transcript tells;
+    /// empty for the main agent and `None` when unknown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_tool_use_id: Option<String>,
 }
 
 impl ToolEvent {
+    /// Name of what is happening: the hook event for non-tool events, else the tool
+    pub fn activity_name(&self) -> String {
+        match self.event_type.label() {
+            Some(label) => label.to_string(),
+            None => self.tool_name.clone(),
+        }
+    }
+
     /// Get a short summary of the tool input for display
     pub fn input_summary(&self) -> String {
+        match self.event_type {
+            EventType::UserPromptSubmit => return truncate_str(&self.prompt, 50),
+            EventType::Notification => return truncate_str(&self.message, 50),
+            _ => {}
+        }
+
         match self.tool_name.as_str() {
             "Read" => {
                 if let Some(input) = &self.tool_input {
@@ -156,7 +209,216 @@ fn truncate_path(path: &str, max_len: usize) -> String {
     }
 }
 
-/// Parse a line from the events JSONL file
-pub fn parse_event(line: &str) -> Option<ToolEvent> {
-    serde_json::from_str(line).ok()
+/// Raw JSON that Claude Code passes to hook commands on stdin
+#[derive(Debug, Deserialize)]
+struct HookPayload {
+    hook_event_name: EventType,
+    session_id: String,
+    #[serde(default)]
+    tool_name: String,
+    #[serde(default)]
+    tool_use_id: Option<String>,
+    #[serde(default)]
+    cwd: String,
+    #[serde(default)]
+    transcript_path: String,
+    #[serde(default)]
+    prompt: String,
+    #[serde(default)]
+    message: String,
+    /// Not sent by Claude Code, but kept if a wrapper script adds one
+    #[serde(default)]
+    timestamp: Option<DateTime<Utc>>,
+    tool_input: Option<serde_json::Value>,
+    tool_response: Option<serde_json::Value>,
+}
+
+impl HookPayload {
+    fn into_event(self, timestamp: DateTime<Utc>, tool_use_id: String) -> ToolEvent {
+        ToolEvent {
+            timestamp,
+            session_id: self.session_id,
+            event_type: self.hook_event_name,
+            tool_name: self.tool_name,
+            tool_use_id,
+            cwd: self.cwd,
+            transcript_path: self.transcript_path,
+            subagent_type: String::new(),
+            subagent_prompt: String::new(),
+            prompt: self.prompt,
+            message: self.message,
+            tool_input: self.tool_input,
+            tool_response: self.tool_response,
+            parent_tool_use_id: None,
+        }
+    }
+
+    /// Hash of what identifies the call apart from when it was made
+    fn call_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.session_id.hash(&mut hasher);
+        self.tool_name.hash(&mut hasher);
+        self.tool_input
+            .as_ref()
+            .map(|input| input.to_string())
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// Why a line from the events file could not be turned into an event
+#[derive(Debug)]
+pub enum ParseError {
+    /// Not valid JSON, such as a line cut short or stray output from a hook script
+    Malformed(serde_json::Error),
+    /// Valid JSON that fits neither the visualiser's format nor a hook payload
+    Schema(serde_json::Error),
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Malformed(err) => write!(f, "malformed JSON: {}", err),
+            ParseError::Schema(err) => write!(f, "unexpected event shape: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Malformed(err) | ParseError::Schema(err) => Some(err),
+        }
+    }
+}
+
+/// Parses the lines of one stream of events, in order.
+///
+/// Accepts both the visualiser's own event format and the raw hook payload
+/// from Claude Code, so a hook can append its stdin to the file unchanged.
+/// Such a hook (`cat >> events.jsonl`) gives no timing though: Claude Code
+/// does not put a time on its payloads, so a recording of them replays as
+/// fast as it can be processed. The `hook` subcommand adds the time.
+#[derive(Debug, Default)]
+pub struct EventParser {
+    /// Time for the first untimed payload of a recording; `None` when reading live
+    recorded_at: Option<DateTime<Utc>>,
+    /// Time of the last event parsed
+    last_timestamp: Option<DateTime<Utc>>,
+    /// Stand-in ids of Tasks started without a `tool_use_id` and not finished yet, by `call_hash`
+    open_tasks: HashMap<u64, VecDeque<String>>,
+}
+
+impl EventParser {
+    /// For events as they arrive, where an untimed payload happened just now
+    pub fn live() -> Self {
+        EventParser::default()
+    }
+
+    /// For a recording last modified at `modified`. An untimed payload takes
+    /// the time of the event before it, or `modified` if it is the first.
+    pub fn recorded(modified: DateTime<Utc>) -> Self {
+        EventParser {
+            recorded_at: Some(modified),
+            ..EventParser::default()
+        }
+    }
+
+    /// Parse line `line_number` of the stream
+    pub fn parse(&mut self, line: &str, line_number: u64) -> Result<ToolEvent, ParseError> {
+        let value: serde_json::Value = serde_json::from_str(line).map_err(ParseError::Malformed)?;
+
+        let event_error = match ToolEvent::deserialize(&value) {
+            Ok(event) => {
+                self.last_timestamp = Some(event.timestamp);
+                return Ok(event);
+            }
+            Err(err) => err,
+        };
+
+        // Report against whichever format the line was evidently meant to be
+        if value.get("hook_event_name").is_none() {
+            return Err(ParseError::Schema(event_error));
+        }
+        let payload = HookPayload::deserialize(&value).map_err(ParseError::Schema)?;
+
+        let timestamp = match (payload.timestamp, self.recorded_at) {
+            (Some(timestamp), _) => timestamp,
+            (None, Some(recorded_at)) => self.last_timestamp.unwrap_or(recorded_at),
+            (None, None) => Utc::now(),
+        };
+        self.last_timestamp = Some(timestamp);
+
+        let tool_use_id = match &payload.tool_use_id {
+            Some(id) => id.clone(),
+            None => self.fallback_tool_use_id(&payload, line_number),
+        };
+        Ok(payload.into_event(timestamp, tool_use_id))
+    }
+
+    /// Stand-in id for payloads from Claude Code versions that omit `tool_use_id`.
+    ///
+    /// Ids are unique to the line, so identical calls are not mistaken for
+    /// duplicates. A Task's PostToolUse takes the id of the earliest unfinished
+    /// Task with the same input, so it still despawns the right subagent.
+    fn fallback_tool_use_id(&mut self, payload: &HookPayload, line_number: u64) -> String {
+        let call = payload.call_hash();
+        let is_task = payload.tool_name == "Task";
+
+        if is_task
+            && payload.hook_event_name == EventType::PostToolUse
+            && let Some(open) = self.open_tasks.get_mut(&call)
+            && let Some(id) = open.pop_front()
+        {
+            if open.is_empty() {
+                self.open_tasks.remove(&call);
+            }
+            return id;
+        }
+
+        let mut hasher = DefaultHasher::new();
+        call.hash(&mut hasher);
+        line_number.hash(&mut hasher);
+        let id = format!("hook-{:016x}", hasher.finish());
+
+        if is_task && payload.hook_event_name == EventType::PreToolUse {
+            self.open_tasks.entry(call).or_default().push_back(id.clone());
+        }
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(event: &str) -> String {
+        format!(
+            r#"{{"hook_event_name":"{}","session_id":"s","tool_name":"Task","tool_input":{{"prompt":"Check the tests"}}}}"#,
+            event
+        )
+    }
+
+    #[test]
+    fn identical_parallel_tasks_without_ids_finish_in_the_order_they_started() {
+        let mut parser = EventParser::recorded(DateTime::UNIX_EPOCH);
+        let first = parser.parse(&payload("PreToolUse"), 1).unwrap();
+        let second = parser.parse(&payload("PreToolUse"), 2).unwrap();
+        assert_ne!(first.tool_use_id, second.tool_use_id);
+
+        assert_eq!(parser.parse(&payload("PostToolUse"), 3).unwrap().tool_use_id, first.tool_use_id);
+        assert_eq!(parser.parse(&payload("PostToolUse"), 4).unwrap().tool_use_id, second.tool_use_id);
+    }
+
+    #[test]
+    fn untimed_payloads_in_a_recording_take_the_time_before_them() {
+        let modified = DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z").unwrap().to_utc();
+        let timed = r#"{"hook_event_name":"Stop","session_id":"s","timestamp":"2025-01-01T10:00:00Z"}"#;
+        let untimed = r#"{"hook_event_name":"Stop","session_id":"s"}"#;
+
+        let mut parser = EventParser::recorded(modified);
+        assert_eq!(parser.parse(untimed, 1).unwrap().timestamp, modified);
+        let at = parser.parse(timed, 2).unwrap().timestamp;
+        assert_eq!(parser.parse(untimed, 3).unwrap().timestamp, at);
+    }
 }
@@ -0,0 +1,51 @@
+use anyhow::Context;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::archive::{self, LoadProblem};
+
+/// What an export wrote and skipped
+#[derive(Debug, Default)]
+pub struct ExportSummary {
+    pub events: usize,
+    pub skipped: usize,
+}
+
+/// Rewrite recorded logs, raw hook payloads included, as one normalized event
+/// per line. Compressed logs and directories of them are read like a replay.
+pub fn export(input: &Path, output: Option<&Path>) -> anyhow::Result<ExportSummary> {
+    let recording = archive::load_recording(input).with_context(|| format!("could not read {}", input.display()))?;
+
+    let mut summary = ExportSummary::default();
+    for problem in &recording.problems {
+        match problem {
+            LoadProblem::Dropped {
+                source,
+                line_number,
+                message,
+            } => {
+                eprintln!("Skipping line {} of {}: {}", line_number, source, message);
+                summary.skipped += 1;
+            }
+            LoadProblem::IoError { source, message } => eprintln!("Error reading {}: {}", source, message),
+        }
+    }
+
+    let writer: Box<dyn Write> = match output {
+        Some(path) => Box::new(
+            File::create(path).with_context(|| format!("could not create {}", path.display()))?,
+        ),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut writer = BufWriter::new(writer);
+
+    for event in &recording.events {
+        serde_json::to_writer(&mut writer, event)?;
+        writer.write_all(b"\n")?;
+        summary.events += 1;
+    }
+
+    writer.flush()?;
+    Ok(summary)
+}
@@ -0,0 +1,193 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use chrono::Utc;
+use std::time::Duration;
+
+use crate::components::*;
+use crate::events::{EventType, ToolEvent};
+use crate::resources::{Diagnostics, EventQueue, GameState, Rooms, StationOccupancy};
+use crate::sprites::{character_names, SpriteAssets};
+use crate::systems::agent::MainAgent;
+use crate::systems::event_reader::ingest_event;
+use crate::systems::{AgentPlugin, ConfigPlugin, EffectsPlugin, MovementPlugin, TimelinePlugin, WorkspacePlugin};
+
+/// Length of one simulated frame
+pub const TICK: Duration = Duration::from_millis(16);
+
+/// The agent systems running without a window or renderer, fed scripted events.
+///
+/// Every `tick` advances time by exactly `TICK`, so movement is reproducible.
+pub struct HeadlessApp {
+    pub app: App,
+}
+
+impl HeadlessApp {
+    pub fn new() -> Self {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(TICK))
+            .insert_resource(placeholder_sprites())
+            .init_resource::<GameState>()
+            .init_resource::<EventQueue>()
+            .init_resource::<Diagnostics>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .add_plugins((
+                ConfigPlugin::default(),
+                WorkspacePlugin,
+                AgentPlugin,
+                MovementPlugin,
+                EffectsPlugin,
+                TimelinePlugin,
+            ));
+
+        // Run a frame so the empty room and its main agent exist before the first event
+        app.update();
+        HeadlessApp { app }
+    }
+
+    /// Queue an event as if it had just been read from the events file
+    pub fn send(&mut self, event: ToolEvent) -> &mut Self {
+        let world = self.app.world_mut();
+        world.resource_scope(|world, mut game_state: Mut<GameState>| {
+            world.resource_scope(|world, mut event_queue: Mut<EventQueue>| {
+                let mut diagnostics = world.resource_mut::<Diagnostics>();
+                ingest_event(event, &mut game_state, &mut event_queue, &mut diagnostics);
+            });
+        });
+        self
+    }
+
+    /// Run `frames` frames
+    pub fn tick(&mut self, frames: usize) -> &mut Self {
+        for _ in 0..frames {
+            self.app.update();
+        }
+        self
+    }
+
+    /// Press and release `key` within one frame
+    pub fn press(&mut self, key: KeyCode) -> &mut Self {
+        self.app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(key);
+        self.app.update();
+        let mut keys = self.app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keys.release(key);
+        keys.clear();
+        self
+    }
+
+    pub fn game_state(&self) -> &GameState {
+        self.app.world().resource::<GameState>()
+    }
+
+    /// Agent entities alive, the main agent included
+    pub fn agent_count(&mut self) -> usize {
+        let world = self.app.world_mut();
+        world.query::<&Agent>().iter(world).count()
+    }
+
+    /// Subagents alive as `(tool_use_id, agent_type, position in their room)`
+    pub fn subagents(&mut self) -> Vec<(String, AgentType, Vec2)> {
+        let world = self.app.world_mut();
+        world
+            .query_filtered::<(&Agent, &Transform), Without<MainAgent>>()
+            .iter(world)
+            .map(|(agent, transform)| {
+                (
+                    agent.tool_use_id.clone().unwrap_or_default(),
+                    agent.agent_type.clone(),
+                    transform.translation.truncate(),
+                )
+            })
+            .collect()
+    }
+
+    /// Sessions shown, one per room in layout order; `None` for the empty room
+    pub fn rooms(&self) -> Vec<Option<String>> {
+        self.app
+            .world()
+            .resource::<Rooms>()
+            .rooms
+            .iter()
+            .map(|room| room.session_id.clone())
+            .collect()
+    }
+
+    /// Main agent of the first room
+    fn main_agent(&self) -> Entity {
+        self.app.world().resource::<Rooms>().rooms[0].main_agent
+    }
+
+    /// Position of the first room's main agent within the room
+    pub fn main_agent_position(&mut self) -> Vec2 {
+        let main_agent = self.main_agent();
+        self.app.world().get::<Transform>(main_agent).unwrap().translation.truncate()
+    }
+
+    pub fn main_agent_station(&mut self) -> Option<StationType> {
+        let main_agent = self.main_agent();
+        self.app.world().get::<CurrentStation>(main_agent).unwrap().station
+    }
+
+    /// Agents at `station` across all rooms
+    pub fn occupancy(&self, station: StationType) -> usize {
+        self.app
+            .world()
+            .resource::<StationOccupancy>()
+            .agents_at_station
+            .iter()
+            .filter(|((_, s), _)| *s == station)
+            .map(|(_, agents)| agents.len())
+            .sum()
+    }
+}
+
+impl Default for HeadlessApp {
+    fn default() -> Self {
+        HeadlessApp::new()
+    }
+}
+
+/// Image handles that point nowhere, so agents can spawn without generating sprites
+fn placeholder_sprites() -> SpriteAssets {
+    SpriteAssets {
+        characters: character_names().map(|name| (name.to_string(), Handle::default())).collect(),
+        ..default()
+    }
+}
+
+/// A bare event in session `session_id`
+pub fn event(session_id: &str, event_type: EventType) -> ToolEvent {
+    ToolEvent {
+        timestamp: Utc::now(),
+        session_id: session_id.to_string(),
+        event_type,
+        tool_name: String::new(),
+        tool_use_id: String::new(),
+        cwd: String::new(),
+        transcript_path: String::new(),
+        subagent_type: String::new(),
+        subagent_prompt: String::new(),
+        prompt: String::new(),
+        message: String::new(),
+        tool_input: None,
+        tool_response: None,
+        parent_tool_use_id: None,
+    }
+}
+
+/// A tool call event, `PreToolUse` or `PostToolUse`
+pub fn tool(session_id: &str, event_type: EventType, tool_name: &str, tool_use_id: &str) -> ToolEvent {
+    ToolEvent {
+        tool_name: tool_name.to_string(),
+        tool_use_id: tool_use_id.to_string(),
+        ..event(session_id, event_type)
+    }
+}
+
+/// A Task call that starts or finishes a subagent of `subagent_type`
+pub fn task(session_id: &str, event_type: EventType, tool_use_id: &str, subagent_type: &str) -> ToolEvent {
+    ToolEvent {
+        subagent_type: subagent_type.to_string(),
+        ..tool(session_id, event_type, "Task", tool_use_id)
+    }
+}
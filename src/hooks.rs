@@ -0,0 +1,431 @@
+use anyhow::{bail, Context};
+use chrono::Utc;
+use serde_json::{json, Map, Value};
+use std::fs::OpenOptions;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Hook events the visualiser listens to
+const HOOK_EVENTS: [&str; 9] = [
+    "PreToolUse",
+    "PostToolUse",
+    "SubagentStop",
+    "UserPromptSubmit",
+    "Stop",
+    "Notification",
+    "SessionStart",
+    "SessionEnd",
+    "PreCompact",
+];
+
+/// Hook events that are matched against a tool name
+const TOOL_HOOK_EVENTS: [&str; 2] = ["PreToolUse", "PostToolUse"];
+
+/// Subcommand that Claude Code runs for each hook
+const HOOK_SUBCOMMAND: &str = "hook";
+
+/// Which settings file to change
+pub enum SettingsScope {
+    /// `~/.claude/settings.json`, for every project
+    User,
+    /// `.claude/settings.json` in the current directory
+    Project,
+    /// A file given explicitly
+    File(PathBuf),
+}
+
+impl SettingsScope {
+    pub fn path(&self) -> anyhow::Result<PathBuf> {
+        match self {
+            SettingsScope::User => {
+                let home = dirs::home_dir().context("could not find the home directory")?;
+                Ok(home.join(".claude").join("settings.json"))
+            }
+            SettingsScope::Project => Ok(PathBuf::from(".claude").join("settings.json")),
+            SettingsScope::File(path) => Ok(path.clone()),
+        }
+    }
+}
+
+/// How to change the settings
+pub struct InstallOptions {
+    pub scope: SettingsScope,
+    /// Events file the hooks should write to, if not the default
+    pub events: Option<PathBuf>,
+    /// Print the change without writing it
+    pub dry_run: bool,
+    /// Remove the visualiser's hooks instead of adding them
+    pub uninstall: bool,
+}
+
+/// Add or remove the visualiser's hooks in a Claude Code settings file.
+///
+/// Other hooks and settings are left alone, and the old file is backed up
+/// next to it before anything is written.
+pub fn install(options: &InstallOptions) -> anyhow::Result<()> {
+    let path = options.scope.path()?;
+    let before = read_settings(&path)?;
+
+    let mut after = before.clone();
+    if options.uninstall {
+        remove_hooks(&mut after)?;
+    } else {
+        let command = hook_command(options.events.as_deref())?;
+        remove_hooks(&mut after)?;
+        add_hooks(&mut after, &command)?;
+    }
+
+    if after == before {
+        println!("{} is already up to date", path.display());
+        return Ok(());
+    }
+
+    let old_text = if path.exists() { to_pretty(&before)? } else { String::new() };
+    let new_text = to_pretty(&after)?;
+
+    if options.dry_run {
+        println!("--- {}", path.display());
+        println!("+++ {} (after)", path.display());
+        print!("{}", line_diff(&old_text, &new_text));
+        return Ok(());
+    }
+
+    if path.exists() {
+        let backup = backup_path(&path);
+        std::fs::copy(&path, &backup)
+            .with_context(|| format!("could not back up {} to {}", path.display(), backup.display()))?;
+        println!("Backed up {} to {}", path.display(), backup.display());
+    } else if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("could not create {}", dir.display()))?;
+    }
+
+    std::fs::write(&path, new_text).with_context(|| format!("could not write {}", path.display()))?;
+    if options.uninstall {
+        println!("Removed the visualiser hooks from {}", path.display());
+    } else {
+        println!("Installed the visualiser hooks in {}", path.display());
+    }
+    Ok(())
+}
+
+/// Read a settings file, treating a missing one as empty
+fn read_settings(path: &Path) -> anyhow::Result<Value> {
+    if !path.exists() {
+        return Ok(Value::Object(Map::new()));
+    }
+
+    let text = std::fs::read_to_string(path).with_context(|| format!("could not read {}", path.display()))?;
+    if text.trim().is_empty() {
+        return Ok(Value::Object(Map::new()));
+    }
+
+    let settings: Value =
+        serde_json::from_str(&text).with_context(|| format!("{} is not valid JSON", path.display()))?;
+    if !settings.is_object() {
+        bail!("{} does not contain a JSON object", path.display());
+    }
+    Ok(settings)
+}
+
+fn to_pretty(settings: &Value) -> anyhow::Result<String> {
+    let mut text = serde_json::to_string_pretty(settings)?;
+    text.push('\n');
+    Ok(text)
+}
+
+/// The command Claude Code should run: this executable's `hook` subcommand
+fn hook_command(events: Option<&Path>) -> anyhow::Result<String> {
+    let exe = std::env::current_exe().context("could not find the visualiser executable")?;
+    let mut command = format!("{} {}", shell_quote(&exe.display().to_string()), HOOK_SUBCOMMAND);
+    if let Some(events) = events {
+        command.push_str(&format!(" --events {}", shell_quote(&events.display().to_string())));
+    }
+    Ok(command)
+}
+
+#[cfg(unix)]
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(not(unix))]
+fn shell_quote(s: &str) -> String {
+    format!("\"{}\"", s)
+}
+
+/// Whether a hook command is one of ours, from this or another install location
+fn is_visualiser_command(command: &str) -> bool {
+    let program = env!("CARGO_PKG_NAME");
+    command.contains(program)
+        && command
+            .split_whitespace()
+            .any(|word| word == HOOK_SUBCOMMAND)
+}
+
+/// The `hooks` object of the settings, created if missing
+fn hooks_object(settings: &mut Value) -> anyhow::Result<&mut Map<String, Value>> {
+    let Some(settings) = settings.as_object_mut() else {
+        bail!("settings are not a JSON object");
+    };
+    let hooks = settings
+        .entry("hooks")
+        .or_insert_with(|| Value::Object(Map::new()));
+    hooks
+        .as_object_mut()
+        .context("the \"hooks\" setting is not a JSON object")
+}
+
+fn add_hooks(settings: &mut Value, command: &str) -> anyhow::Result<()> {
+    let hooks = hooks_object(settings)?;
+
+    for event in HOOK_EVENTS {
+        let mut matcher = Map::new();
+        if TOOL_HOOK_EVENTS.contains(&event) {
+            matcher.insert("matcher".to_string(), json!("*"));
+        }
+        matcher.insert(
+            "hooks".to_string(),
+            json!([{ "type": "command", "command": command }]),
+        );
+
+        let entries = hooks
+            .entry(event)
+            .or_insert_with(|| Value::Array(Vec::new()));
+        let Some(entries) = entries.as_array_mut() else {
+            bail!("the \"{}\" hooks setting is not a list", event);
+        };
+        entries.push(Value::Object(matcher));
+    }
+
+    Ok(())
+}
+
+/// Remove every hook command of ours, and any matcher or event left empty by that
+fn remove_hooks(settings: &mut Value) -> anyhow::Result<()> {
+    let has_hooks = settings.as_object().is_some_and(|settings| settings.contains_key("hooks"));
+    if !has_hooks {
+        return Ok(());
+    }
+    let hooks = hooks_object(settings)?;
+
+    for event in HOOK_EVENTS {
+        let Some(entries) = hooks.get_mut(event).and_then(Value::as_array_mut) else {
+            continue;
+        };
+
+        for entry in entries.iter_mut() {
+            if let Some(commands) = entry.get_mut("hooks").and_then(Value::as_array_mut) {
+                commands.retain(|hook| {
+                    !hook
+                        .get("command")
+                        .and_then(Value::as_str)
+                        .is_some_and(is_visualiser_command)
+                });
+            }
+        }
+        entries.retain(|entry| {
+            entry
+                .get("hooks")
+                .and_then(Value::as_array)
+                .is_none_or(|commands| !commands.is_empty())
+        });
+
+        if entries.is_empty() {
+            hooks.remove(event);
+        }
+    }
+
+    if hooks.is_empty()
+        && let Some(settings) = settings.as_object_mut()
+    {
+        settings.remove("hooks");
+    }
+
+    Ok(())
+}
+
+/// Where to back up a settings file, never an existing backup
+fn backup_path(path: &Path) -> PathBuf {
+    let stamp = Utc::now().format("%Y%m%d%H%M%S");
+    let mut attempt = 0;
+    loop {
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".bak-{}", stamp));
+        if attempt > 0 {
+            name.push(format!("-{}", attempt));
+        }
+        let backup = path.with_file_name(name);
+        if !backup.exists() {
+            return backup;
+        }
+        attempt += 1;
+    }
+}
+
+/// Lines removed and added between two texts, prefixed with `-` and `+`
+fn line_diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence lengths of every pair of suffixes
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            diff.push_str(&format!(" {}\n", old[i]));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || common[i][j + 1] >= common[i + 1][j]) {
+            diff.push_str(&format!("+{}\n", new[j]));
+            j += 1;
+        } else {
+            diff.push_str(&format!("-{}\n", old[i]));
+            i += 1;
+        }
+    }
+    diff
+}
+
+/// Append the hook payload on stdin to the events file.
+///
+/// Claude Code does not put a time on hook payloads, so one is added here.
+/// Errors are reported but never fail the hook, which would interrupt Claude.
+pub fn run_hook(events: &Path) {
+    if let Err(err) = append_payload(events) {
+        eprintln!("agentic-visualiser hook: {:#}", err);
+    }
+}
+
+fn append_payload(path: &Path) -> anyhow::Result<()> {
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+
+    let mut payload: Value = serde_json::from_str(&input).context("hook input is not JSON")?;
+    if let Some(payload) = payload.as_object_mut() {
+        payload
+            .entry("timestamp")
+            .or_insert_with(|| json!(Utc::now()));
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    // One write per line keeps concurrent hooks from interleaving
+    let mut line = serde_json::to_string(&payload)?;
+    line.push('\n');
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("could not open {}", path.display()))?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMMAND: &str = "'/usr/local/bin/agentic-visualiser' hook";
+
+    fn foreign_settings() -> Value {
+        json!({
+            "model": "opus",
+            "hooks": {
+                "PreToolUse": [
+                    { "matcher": "Bash", "hooks": [{ "type": "command", "command": "audit-bash" }] }
+                ],
+                "PostToolUse": [
+                    { "matcher": "*", "hooks": [{ "type": "command", "command": "notify-send done" }] }
+                ]
+            }
+        })
+    }
+
+    fn commands(settings: &Value, event: &str) -> Vec<String> {
+        settings["hooks"][event]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|entry| entry["hooks"].as_array().into_iter().flatten())
+            .filter_map(|hook| hook["command"].as_str().map(str::to_string))
+            .collect()
+    }
+
+    #[test]
+    fn installing_adds_to_the_hooks_already_there() {
+        let mut settings = foreign_settings();
+        add_hooks(&mut settings, COMMAND).unwrap();
+
+        assert_eq!(settings["model"], "opus");
+        assert_eq!(commands(&settings, "PreToolUse"), vec!["audit-bash", COMMAND]);
+        assert_eq!(commands(&settings, "PostToolUse"), vec!["notify-send done", COMMAND]);
+        assert_eq!(commands(&settings, "SessionEnd"), vec![COMMAND]);
+        assert_eq!(settings["hooks"]["PreToolUse"][1]["matcher"], "*");
+        assert!(settings["hooks"]["Stop"][0].get("matcher").is_none());
+    }
+
+    #[test]
+    fn installing_twice_does_not_add_the_hooks_twice() {
+        let mut once = foreign_settings();
+        remove_hooks(&mut once).unwrap();
+        add_hooks(&mut once, COMMAND).unwrap();
+
+        let mut twice = once.clone();
+        remove_hooks(&mut twice).unwrap();
+        add_hooks(&mut twice, COMMAND).unwrap();
+        assert_eq!(twice, once);
+
+        // A reinstall from another location replaces the old command
+        let mut moved = once.clone();
+        remove_hooks(&mut moved).unwrap();
+        add_hooks(&mut moved, "'/opt/agentic-visualiser' hook").unwrap();
+        assert_eq!(commands(&moved, "PreToolUse"), vec!["audit-bash", "'/opt/agentic-visualiser' hook"]);
+    }
+
+    #[test]
+    fn uninstalling_leaves_other_hooks_alone() {
+        let mut settings = foreign_settings();
+        add_hooks(&mut settings, COMMAND).unwrap();
+        remove_hooks(&mut settings).unwrap();
+        assert_eq!(settings, foreign_settings());
+
+        // Nothing else there, so nothing is left behind
+        let mut ours_only = json!({ "model": "opus" });
+        add_hooks(&mut ours_only, COMMAND).unwrap();
+        remove_hooks(&mut ours_only).unwrap();
+        assert_eq!(ours_only, json!({ "model": "opus" }));
+    }
+
+    #[test]
+    fn only_hook_commands_of_the_visualiser_count_as_ours() {
+        assert!(is_visualiser_command(COMMAND));
+        assert!(is_visualiser_command("agentic-visualiser hook --events /tmp/e.jsonl"));
+        assert!(!is_visualiser_command("agentic-visualiser install"));
+        assert!(!is_visualiser_command("my-hook"));
+    }
+
+    #[test]
+    fn the_dry_run_diff_marks_added_and_removed_lines() {
+        let old = "{\n  \"model\": \"opus\",\n  \"theme\": \"dark\"\n}\n";
+        let new = "{\n  \"model\": \"opus\",\n  \"hooks\": {}\n}\n";
+
+        assert_eq!(
+            line_diff(old, new),
+            " {\n   \"model\": \"opus\",\n+  \"hooks\": {}\n-  \"theme\": \"dark\"\n }\n"
+        );
+        assert_eq!(line_diff("", "{}\n"), "+{}\n");
+    }
+}
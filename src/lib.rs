@@ -0,0 +1,57 @@
+//! The Claude Code office: hook events in, a little pixel-art workplace out.
+//!
+//! `events` holds the event model and parser, `domain` what the events mean
+//! for sessions and agents, and `VisualiserPlugin` everything needed to draw it
+//! inside an existing Bevy app. Add one event source next to it:
+//! `EventReaderPlugin` for live hooks, `ReplayPlugin` or `DemoPlugin`.
+
+pub mod agent_types;
+pub mod archive;
+pub mod components;
+pub mod config;
+pub mod demo;
+pub mod domain;
+pub mod events;
+pub mod export;
+#[cfg(any(test, feature = "harness"))]
+pub mod harness;
+pub mod hooks;
+pub mod resources;
+pub mod sources;
+pub mod sprites;
+pub mod systems;
+mod tail;
+pub mod transcript;
+
+use bevy::app::{PluginGroup, PluginGroupBuilder};
+
+pub use agent_types::{AgentType, AgentTypes};
+pub use components::StationType;
+pub use events::{EventParser, EventType, ParseError, ToolEvent};
+pub use systems::{DemoPlugin, EventReaderPlugin, ReplayPlugin};
+
+/// Everything that draws the office, without a camera or a source of events.
+///
+/// Expects the app to have `DefaultPlugins` or equivalent rendering, asset and
+/// UI plugins. Parts can be left out with `PluginGroupBuilder::disable`, such
+/// as `UIPlugin` when the host app has its own status display. `ConfigPlugin`
+/// only watches the config file when replaced with `PluginGroupBuilder::set`.
+pub struct VisualiserPlugin;
+
+impl PluginGroup for VisualiserPlugin {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(systems::ConfigPlugin::default())
+            .add(sprites::SpriteGenPlugin)
+            .add(systems::WorkspacePlugin)
+            .add(systems::AgentPlugin)
+            .add(systems::MovementPlugin)
+            .add(systems::AnimationPlugin)
+            .add(systems::UIPlugin)
+            .add(systems::SessionPanelPlugin)
+            .add(systems::TimelinePlugin)
+            .add(systems::EffectsPlugin)
+            .add(systems::HierarchyPlugin)
+            .add(systems::DiagnosticsPlugin)
+    }
+}
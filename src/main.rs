@@ -1,137 +1,109 @@
-mod components;
-mod events;
-mod resources;
-mod sprites;
-mod systems;
+mod cli;
 
 use bevy::prelude::*;
+use clap::Parser;
 
-use components::*;
-use resources::StationPositions;
-use sprites::{SpriteAssets, SpriteGenPlugin};
-use systems::{
-    AgentPlugin, AnimationPlugin, EventReaderPlugin, MovementPlugin, UIPlugin,
-};
+use agentic_visualiser::config::{config_path, Config};
+use agentic_visualiser::resources::{EventQueue, DEFAULT_DEDUP_WINDOW};
+use agentic_visualiser::sources::default_events_path;
+use agentic_visualiser::systems::ConfigPlugin;
+use agentic_visualiser::{demo, export, hooks};
+use agentic_visualiser::{DemoPlugin, EventReaderPlugin, ReplayPlugin, VisualiserPlugin};
+use cli::{Cli, Command};
 
-fn main() {
-    App::new()
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
+    match cli.command.unwrap_or_default() {
+        Command::Watch(args) => {
+            let config = load_config();
+            let events = default_events_path(config.events.path.as_deref());
+            let mut app = build_app(config, args.dedup_window);
+            app.add_plugins(EventReaderPlugin::from_specs(&args.source_specs(&events)));
+            app.run();
+        }
+        Command::Replay(args) => {
+            let mut app = build_app(load_config(), DEFAULT_DEDUP_WINDOW);
+            app.add_plugins(ReplayPlugin {
+                path: args.file,
+                speed: args.speed,
+            });
+            app.run();
+        }
+        Command::Demo(args) => {
+            let seed = args.seed.unwrap_or_else(rand::random);
+            if let Some(output) = &args.output {
+                demo::write_events(seed, args.count, output)?;
+                eprintln!("Wrote {} demo events with seed {} to {}", args.count, seed, output.display());
+                return Ok(());
+            }
+
+            info!("Playing demo session with seed {}", seed);
+            let mut app = build_app(load_config(), DEFAULT_DEDUP_WINDOW);
+            app.add_plugins(DemoPlugin { seed, speed: args.speed });
+            app.run();
+        }
+        Command::Export(args) => {
+            let summary = export::export(&args.input, args.output.as_deref())?;
+            eprintln!("Exported {} events, skipped {} lines", summary.events, summary.skipped);
+        }
+        Command::InstallHooks(args) => hooks::install(&args.options())?,
+        Command::Hook(args) => {
+            // Run for every tool call, so the config is only read when nothing else says where to write.
+            // A broken config must not get in the way of Claude Code, so it quietly counts as none
+            let events = args.events.unwrap_or_else(|| {
+                let config = Config::load(&config_path()).unwrap_or_default();
+                default_events_path(config.events.path.as_deref())
+            });
+            hooks::run_hook(&events);
+        }
+    }
+
+    Ok(())
+}
+
+/// The settings from the config file, with problems reported on stderr
+/// since logging only starts with the app
+fn load_config() -> Config {
+    let path = config_path();
+    let mut config = Config::load(&path).unwrap_or_else(|e| {
+        eprintln!("{:#}; using the default settings", e);
+        Config::default()
+    });
+    for problem in config.fix_invalid() {
+        eprintln!("{}: {}", path.display(), problem);
+    }
+    config
+}
+
+/// The windowed app with everything but a source of events, set up from `config`
+fn build_app(config: Config, dedup_window: usize) -> App {
+    let path = config_path();
+
+    let mut app = App::new();
+    app
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 title: "Claude Code Visualiser".to_string(),
-                resolution: (900.0, 700.0).into(),
+                resolution: (config.window.width, config.window.height).into(),
                 resizable: true,
                 ..default()
             }),
             ..default()
         }).set(ImagePlugin::default_nearest())) // Pixel art filtering
         // Background color
-        .insert_resource(ClearColor(Color::srgb(0.08, 0.08, 0.12)))
-        // Our plugins
-        .add_plugins((
-            SpriteGenPlugin,
-            EventReaderPlugin,
-            AgentPlugin,
-            MovementPlugin,
-            AnimationPlugin,
-            UIPlugin,
-        ))
+        .insert_resource(ClearColor(config.window.clear_color()))
+        // Inserted before the plugins so their `init_resource` keeps these
+        .insert_resource(EventQueue::with_dedup_window(dedup_window))
+        .insert_resource(config)
+        // Our plugins, reloading the settings when the file changes
+        .add_plugins(VisualiserPlugin.build().set(ConfigPlugin { watch: Some(path) }))
         // Setup systems
-        .add_systems(Startup, setup_camera)
-        .add_systems(Startup, setup_workspace.after(sprites::generate_sprites))
-        .run();
+        .add_systems(Startup, setup_camera);
+    app
 }
 
 /// Set up the 2D camera
 fn setup_camera(mut commands: Commands) {
     commands.spawn(Camera2d::default());
 }
-
-/// Set up the workspace with stations
-pub fn setup_workspace(
-    mut commands: Commands,
-    station_positions: Res<StationPositions>,
-    sprite_assets: Res<SpriteAssets>,
-) {
-    // Background workspace area - darker floor
-    commands.spawn((
-        Sprite {
-            color: Color::srgba(0.15, 0.15, 0.2, 0.8),
-            custom_size: Some(Vec2::new(750.0, 550.0)),
-            ..default()
-        },
-        Transform::from_xyz(0.0, 0.0, 0.0),
-    ));
-
-    // Grid pattern on floor
-    for i in -5..6 {
-        // Vertical lines
-        commands.spawn((
-            Sprite {
-                color: Color::srgba(0.25, 0.25, 0.3, 0.3),
-                custom_size: Some(Vec2::new(2.0, 550.0)),
-                ..default()
-            },
-            Transform::from_xyz(i as f32 * 70.0, 0.0, 0.1),
-        ));
-        // Horizontal lines
-        commands.spawn((
-            Sprite {
-                color: Color::srgba(0.25, 0.25, 0.3, 0.3),
-                custom_size: Some(Vec2::new(750.0, 2.0)),
-                ..default()
-            },
-            Transform::from_xyz(0.0, i as f32 * 55.0, 0.1),
-        ));
-    }
-
-    // Spawn stations with pixel art sprites
-    spawn_station(&mut commands, &station_positions, &sprite_assets, StationType::Library);
-    spawn_station(&mut commands, &station_positions, &sprite_assets, StationType::Desk);
-    spawn_station(&mut commands, &station_positions, &sprite_assets, StationType::Terminal);
-    spawn_station(&mut commands, &station_positions, &sprite_assets, StationType::WebPortal);
-    spawn_station(&mut commands, &station_positions, &sprite_assets, StationType::MeetingArea);
-}
-
-/// Spawn a workstation with pixel art
-fn spawn_station(
-    commands: &mut Commands,
-    positions: &StationPositions,
-    sprite_assets: &SpriteAssets,
-    station_type: StationType,
-) {
-    let pos = positions.get(station_type);
-
-    // Get the sprite handle for this station
-    if let Some(image_handle) = sprite_assets.stations.get(&station_type) {
-        commands
-            .spawn((
-                Sprite {
-                    image: image_handle.clone(),
-                    custom_size: Some(Vec2::new(96.0, 72.0)), // Scale up 1.5x
-                    ..default()
-                },
-                Transform::from_xyz(pos.x, pos.y, 1.0),
-                Station { station_type },
-            ))
-            .with_children(|parent| {
-                // Station label with background
-                parent.spawn((
-                    Sprite {
-                        color: Color::srgba(0.0, 0.0, 0.0, 0.7),
-                        custom_size: Some(Vec2::new(70.0, 20.0)),
-                        ..default()
-                    },
-                    Transform::from_xyz(0.0, -50.0, 1.5),
-                ));
-                parent.spawn((
-                    Text2d::new(station_type.label()),
-                    TextFont {
-                        font_size: 14.0,
-                        ..default()
-                    },
-                    TextColor(Color::WHITE),
-                    Transform::from_xyz(0.0, -50.0, 2.0),
-                ));
-            });
-    }
-}
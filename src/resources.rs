@@ -1,47 +1,270 @@
 use bevy::prelude::*;
-use std::collections::{HashMap, VecDeque};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
 
+use crate::agent_types::AgentTypes;
 use crate::components::StationType;
+use crate::domain::Sessions;
 use crate::events::ToolEvent;
 
+/// What has been read about one session, whether or not its room is still shown
+#[derive(Debug, Clone)]
+pub struct SessionInfo {
+    pub session_id: String,
+    /// Project directory, from the latest event that had one
+    pub cwd: Option<String>,
+    pub started: DateTime<Utc>,
+    pub last_activity: DateTime<Utc>,
+    /// Tool calls started, including those only known from the transcript
+    pub tool_calls: usize,
+    /// What the session is doing and a short summary of it
+    pub activity: Option<(String, String)>,
+    /// Its room faded out after the session went quiet
+    pub closed: bool,
+}
+
+/// Sessions listed after their room has closed before the older ones are collapsed
+pub const SHOWN_CLOSED_SESSIONS: usize = 5;
+
 /// Shared game state resource
 #[derive(Resource, Default)]
 pub struct GameState {
+    /// Session in focus, shown in the status bar; follows the latest event unless pinned
     pub session_id: Option<String>,
-    pub current_tool: Option<String>,
-    pub current_tool_input: Option<String>,
+    /// Keep the focus on `session_id` whatever other sessions do
+    pub pinned: bool,
+    /// Every session seen, in the order they first appeared
+    pub sessions: Vec<SessionInfo>,
     pub agent_count: usize,
     pub events_processed: usize,
-    /// Flag indicating session changed - agents should be cleared
-    pub session_changed: bool,
+    /// Elapsed time at which the events file was last truncated or replaced
+    pub log_rotated_at: Option<f32>,
+}
+
+impl GameState {
+    pub fn session(&self, session_id: &str) -> Option<&SessionInfo> {
+        self.sessions.iter().find(|info| info.session_id == session_id)
+    }
+
+    pub fn focused_session(&self) -> Option<&SessionInfo> {
+        self.session(self.session_id.as_deref()?)
+    }
+
+    /// The session's info, starting it at `at` if it is new
+    pub fn session_entry(&mut self, session_id: &str, at: DateTime<Utc>) -> &mut SessionInfo {
+        let index = match self.sessions.iter().position(|info| info.session_id == session_id) {
+            Some(index) => index,
+            None => {
+                self.sessions.push(SessionInfo {
+                    session_id: session_id.to_string(),
+                    cwd: None,
+                    started: at,
+                    last_activity: at,
+                    tool_calls: 0,
+                    activity: None,
+                    closed: false,
+                });
+                self.sessions.len() - 1
+            }
+        };
+        &mut self.sessions[index]
+    }
+
+    /// Sessions in sidebar order: those with a room first, then closed ones, latest first
+    pub fn listed(&self) -> Vec<&SessionInfo> {
+        let mut closed: Vec<&SessionInfo> = self.sessions.iter().filter(|info| info.closed).collect();
+        closed.sort_by_key(|info| std::cmp::Reverse(info.last_activity));
+        self.sessions.iter().filter(|info| !info.closed).chain(closed).collect()
+    }
+
+    /// Mark a session's room as closed, moving it down the list
+    pub fn close(&mut self, session_id: &str) {
+        if let Some(info) = self.sessions.iter_mut().find(|info| info.session_id == session_id) {
+            info.closed = true;
+        }
+    }
+
+    /// Move the focus to a session that just did something, unless another is pinned
+    pub fn follow(&mut self, session_id: &str) {
+        if !self.pinned {
+            self.session_id = Some(session_id.to_string());
+        }
+    }
+
+    /// Focus on `session_id` and keep it there
+    pub fn pin(&mut self, session_id: &str) {
+        self.session_id = Some(session_id.to_string());
+        self.pinned = true;
+    }
+
+    /// Let the focus follow the latest event again
+    pub fn unpin(&mut self) {
+        self.pinned = false;
+    }
+}
+
+/// Whether the session list is shown; automatic until toggled with Tab
+#[derive(Resource, Default)]
+pub struct SessionList {
+    /// Chosen with Tab; `None` shows the list once there is more than one session
+    pub shown: Option<bool>,
+    /// List every closed session, not just the latest `SHOWN_CLOSED_SESSIONS`
+    pub expanded: bool,
+}
+
+impl SessionList {
+    pub fn is_visible(&self, session_count: usize) -> bool {
+        self.shown.unwrap_or(session_count > 1)
+    }
+
+    /// Sessions with a row in the sidebar, in order, and how many older closed ones are collapsed
+    pub fn rows<'a>(&self, game_state: &'a GameState) -> (Vec<&'a SessionInfo>, usize) {
+        let mut rows = game_state.listed();
+        let closed = rows.iter().filter(|info| info.closed).count();
+        let collapsed = if self.expanded {
+            0
+        } else {
+            closed.saturating_sub(SHOWN_CLOSED_SESSIONS)
+        };
+        rows.truncate(rows.len() - collapsed);
+        (rows, collapsed)
+    }
+}
+
+/// How many recent events are remembered for deduplication by default
+pub const DEFAULT_DEDUP_WINDOW: usize = 1000;
+
+/// Remembers the most recent event keys, forgetting the oldest once full
+pub struct DedupWindow {
+    capacity: usize,
+    keys: HashSet<String>,
+    /// Keys in insertion order, oldest first
+    order: VecDeque<String>,
+    /// Events dropped because their key was still in the window
+    pub suppressed: usize,
+}
+
+impl DedupWindow {
+    pub fn new(capacity: usize) -> Self {
+        // A window of zero could never hold the key it is checking against
+        let capacity = capacity.max(1);
+        DedupWindow {
+            capacity,
+            keys: HashSet::with_capacity(capacity),
+            order: VecDeque::with_capacity(capacity),
+            suppressed: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Remember `key`, returning false if it is already in the window
+    pub fn insert(&mut self, key: String) -> bool {
+        if self.keys.contains(&key) {
+            self.suppressed += 1;
+            return false;
+        }
+
+        // Forget the oldest key to make room
+        if self.order.len() == self.capacity
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.keys.remove(&oldest);
+        }
+        self.keys.insert(key.clone());
+        self.order.push_back(key);
+        true
+    }
+}
+
+impl Default for DedupWindow {
+    fn default() -> Self {
+        DedupWindow::new(DEFAULT_DEDUP_WINDOW)
+    }
+}
+
+/// The office as worked out from the events processed so far
+#[derive(Resource, Default)]
+pub struct Office(pub Sessions);
+
+/// One room of the office, showing one session
+pub struct Room {
+    /// Session shown here; `None` for the empty room shown before any events
+    pub session_id: Option<String>,
+    /// Root entity; the floor, stations and agents are its children
+    pub entity: Entity,
+    pub floor: Entity,
+    /// Name of the session above the floor
+    pub label: Entity,
+    pub main_agent: Entity,
+    /// Whether the lights are on; goes dark between SessionEnd and SessionStart
+    pub open: bool,
+    /// Elapsed time when the session last had an event
+    pub last_activity: f32,
+    /// Whether the room is fading out for lack of activity
+    pub fading: bool,
+}
+
+/// Rooms currently in the office, in the order they are laid out
+#[derive(Resource, Default)]
+pub struct Rooms {
+    pub rooms: Vec<Room>,
+}
+
+impl Rooms {
+    pub fn for_session(&self, session_id: &str) -> Option<&Room> {
+        self.rooms
+            .iter()
+            .find(|room| room.session_id.as_deref() == Some(session_id))
+    }
+
+    pub fn for_session_mut(&mut self, session_id: &str) -> Option<&mut Room> {
+        self.rooms
+            .iter_mut()
+            .find(|room| room.session_id.as_deref() == Some(session_id))
+    }
+
+    /// A room not yet showing any session
+    pub fn unclaimed_mut(&mut self) -> Option<&mut Room> {
+        self.rooms.iter_mut().find(|room| room.session_id.is_none())
+    }
 }
 
 /// Queue of tool events to process
 #[derive(Resource, Default)]
 pub struct EventQueue {
     pub events: VecDeque<ToolEvent>,
-    /// Track recently seen events to deduplicate
-    pub seen_events: std::collections::HashSet<String>,
+    /// Recently seen events, to drop hooks that fire twice
+    pub dedup: DedupWindow,
 }
 
 impl EventQueue {
-    pub fn push(&mut self, event: ToolEvent) {
-        // Create a unique key for deduplication
-        let key = format!("{}-{:?}", event.tool_use_id, event.event_type);
-
-        // Skip if we've seen this exact event recently
-        if self.seen_events.contains(&key) {
-            return;
+    /// A queue that remembers the last `capacity` events for deduplication
+    pub fn with_dedup_window(capacity: usize) -> Self {
+        EventQueue {
+            events: VecDeque::new(),
+            dedup: DedupWindow::new(capacity),
         }
+    }
 
-        self.seen_events.insert(key);
+    /// Queue an event, returning false if it was dropped as a duplicate
+    pub fn push(&mut self, event: ToolEvent) -> bool {
+        // Events that are not about a tool call have nothing to tell them apart
+        if event.tool_use_id.is_empty() {
+            self.events.push_back(event);
+            return true;
+        }
 
-        // Keep seen_events from growing too large
-        if self.seen_events.len() > 1000 {
-            self.seen_events.clear();
+        let key = format!("{}-{:?}", event.tool_use_id, event.event_type);
+        if !self.dedup.insert(key) {
+            return false;
         }
 
         self.events.push_back(event);
+        true
     }
 
     pub fn pop(&mut self) -> Option<ToolEvent> {
@@ -49,6 +272,170 @@ impl EventQueue {
     }
 }
 
+/// How many recent failures the diagnostics overlay lists
+const MAX_RECENT_FAILURES: usize = 5;
+
+/// Something that went wrong while reading events
+#[derive(Debug, Clone)]
+pub struct Failure {
+    /// Where it happened, such as a file path or `stdin`
+    pub source: String,
+    pub line_number: Option<u64>,
+    pub message: String,
+}
+
+/// Counters for everything read but not shown, plus the latest failures
+#[derive(Resource, Default)]
+pub struct Diagnostics {
+    pub lines_read: usize,
+    /// Lines that could not be parsed into an event
+    pub dropped: usize,
+    /// Events with a hook event name this version does not know
+    pub unknown: usize,
+    pub io_errors: usize,
+    /// Newest last
+    pub recent: VecDeque<Failure>,
+    /// Whether the overlay is showing
+    pub visible: bool,
+}
+
+impl Diagnostics {
+    /// Count a line that could not be parsed
+    pub fn record_dropped(&mut self, source: &str, line_number: u64, message: String) {
+        self.dropped += 1;
+        self.record_failure(Failure {
+            source: source.to_string(),
+            line_number: Some(line_number),
+            message,
+        });
+    }
+
+    /// Count a failure to read from a source
+    pub fn record_io_error(&mut self, source: &str, message: String) {
+        self.io_errors += 1;
+        self.record_failure(Failure {
+            source: source.to_string(),
+            line_number: None,
+            message,
+        });
+    }
+
+    fn record_failure(&mut self, failure: Failure) {
+        if self.recent.len() == MAX_RECENT_FAILURES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(failure);
+    }
+}
+
+/// How many events are played per frame by default, leaving frames to render spawns
+pub const DEFAULT_EVENTS_PER_FRAME: usize = 3;
+
+/// How many processed events are kept for seeking by default
+pub const DEFAULT_HISTORY_LIMIT: usize = 20_000;
+
+/// The latest events processed, plus playback controls over that history
+#[derive(Resource)]
+pub struct Timeline {
+    /// Processed events in order, without their tool responses; the current position is its length
+    pub history: Vec<ToolEvent>,
+    /// State before the oldest event still in `history`, which seeking starts from
+    pub baseline: Sessions,
+    /// Most events kept in `history`; older ones are folded into `baseline`
+    pub history_limit: usize,
+    /// Events folded into `baseline` so far
+    pub dropped: usize,
+    /// Sessions whose room closed, with how many events had been processed by then
+    pub closures: VecDeque<(usize, String)>,
+    pub paused: bool,
+    /// Events still to let through one at a time while paused
+    pub steps_requested: usize,
+    /// Position to jump to, applied before the next events are processed
+    pub seek_to: Option<usize>,
+    /// Most events processed in one frame while playing
+    pub events_per_frame: usize,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Timeline {
+            history: Vec::new(),
+            baseline: Sessions::default(),
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            dropped: 0,
+            closures: VecDeque::new(),
+            paused: false,
+            steps_requested: 0,
+            seek_to: None,
+            events_per_frame: DEFAULT_EVENTS_PER_FRAME,
+        }
+    }
+}
+
+impl Timeline {
+    pub fn position(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Add a processed event to the history, dropping the oldest past the limit
+    pub fn record(&mut self, event: &ToolEvent) {
+        // Nothing replays tool responses, and they can be megabytes each
+        self.history.push(ToolEvent {
+            tool_response: None,
+            ..event.clone()
+        });
+
+        let excess = self.history.len().saturating_sub(self.history_limit);
+        for event in self.history.drain(..excess) {
+            while self.closures.front().is_some_and(|(at, _)| *at <= self.dropped) {
+                let (_, session_id) = self.closures.pop_front().unwrap();
+                self.baseline.close(&session_id);
+            }
+            self.baseline.apply(&event);
+            self.dropped += 1;
+        }
+    }
+
+    /// Note that a session's room closed at the current position, so seeking does not bring it back
+    pub fn close(&mut self, session_id: &str) {
+        let at = self.dropped + self.history.len();
+        self.closures.push_back((at, session_id.to_string()));
+    }
+
+    /// Forget rooms that closed after the current position; they close again once idle
+    pub fn forget_later_closures(&mut self) {
+        let position = self.dropped + self.history.len();
+        self.closures.retain(|(at, _)| *at <= position);
+    }
+
+    /// The sessions as they were at the current position
+    pub fn replay(&self, agent_types: Arc<AgentTypes>) -> Sessions {
+        let mut sessions = self.baseline.clone();
+        sessions.set_agent_types(agent_types);
+        let mut closures = self.closures.iter().peekable();
+        for (index, event) in self.history.iter().enumerate() {
+            while let Some((_, session_id)) = closures.next_if(|(at, _)| *at <= self.dropped + index) {
+                sessions.close(session_id);
+            }
+            sessions.apply(event);
+        }
+        for (_, session_id) in closures {
+            sessions.close(session_id);
+        }
+        sessions
+    }
+
+    /// How many events may be processed this frame
+    pub fn take_budget(&mut self) -> usize {
+        if !self.paused {
+            return self.events_per_frame;
+        }
+        let steps = self.steps_requested.min(self.events_per_frame);
+        self.steps_requested -= steps;
+        steps
+    }
+}
+
 /// Station positions in the workspace
 #[derive(Resource)]
 pub struct StationPositions {
@@ -88,44 +475,32 @@ impl StationPositions {
     }
 }
 
-/// File watcher state
+/// Default distance of agents from the centre of a shared station
+pub const DEFAULT_ORBIT_RADIUS: f32 = 45.0;
+
+/// Tracks agents at each station of each room for orbital positioning
 #[derive(Resource)]
-pub struct FileWatcherState {
-    pub events_path: std::path::PathBuf,
-    pub last_position: u64,
+pub struct StationOccupancy {
+    /// Maps a room and station type to list of agent entity IDs currently there
+    pub agents_at_station: HashMap<(Entity, StationType), Vec<Entity>>,
+    /// Distance from the station centre when agents share it
+    pub orbit_radius: f32,
 }
 
-impl Default for FileWatcherState {
+impl Default for StationOccupancy {
     fn default() -> Self {
-        let events_path = dirs::home_dir()
-            .unwrap_or_else(|| std::path::PathBuf::from("."))
-            .join(".claude-visualizer")
-            .join("events.jsonl");
-
-        // Start from end of file to skip historical events
-        let last_position = std::fs::metadata(&events_path)
-            .map(|m| m.len())
-            .unwrap_or(0);
-
-        FileWatcherState {
-            events_path,
-            last_position,
+        StationOccupancy {
+            agents_at_station: HashMap::new(),
+            orbit_radius: DEFAULT_ORBIT_RADIUS,
         }
     }
 }
 
-/// Tracks agents at each station for orbital positioning
-#[derive(Resource, Default)]
-pub struct StationOccupancy {
-    /// Maps station type to list of agent entity IDs currently there
-    pub agents_at_station: HashMap<StationType, Vec<Entity>>,
-}
-
 impl StationOccupancy {
     /// Calculate orbital position for an agent at a station
     /// Returns an offset from the station center
-    pub fn get_orbital_offset(&self, station: StationType, agent_entity: Entity) -> Vec2 {
-        let agents = self.agents_at_station.get(&station).map(|v| v.as_slice()).unwrap_or(&[]);
+    pub fn get_orbital_offset(&self, room: Entity, station: StationType, agent_entity: Entity) -> Vec2 {
+        let agents = self.agents_at_station.get(&(room, station)).map(|v| v.as_slice()).unwrap_or(&[]);
 
         if agents.len() <= 1 {
             return Vec2::ZERO;
@@ -135,7 +510,7 @@ impl StationOccupancy {
         let index = agents.iter().position(|&e| e == agent_entity).unwrap_or(0);
 
         // Calculate orbital position
-        let orbit_radius = 45.0; // Distance from center
+        let orbit_radius = self.orbit_radius;
         let total_agents = agents.len();
         let angle = (index as f32 / total_agents as f32) * std::f32::consts::TAU;
 
@@ -146,11 +521,11 @@ impl StationOccupancy {
     }
 
     /// Add an agent to a station
-    pub fn add_agent(&mut self, station: StationType, entity: Entity) {
+    pub fn add_agent(&mut self, room: Entity, station: StationType, entity: Entity) {
         // First remove from any other station
         self.remove_agent(entity);
         // Then add to new station
-        self.agents_at_station.entry(station).or_default().push(entity);
+        self.agents_at_station.entry((room, station)).or_default().push(entity);
     }
 
     /// Remove an agent from all stations
@@ -160,8 +535,13 @@ impl StationOccupancy {
         }
     }
 
+    /// Forget every agent in a room that is going away
+    pub fn remove_room(&mut self, room: Entity) {
+        self.agents_at_station.retain(|(r, _), _| *r != room);
+    }
+
     /// Get the count of agents at a station
-    pub fn count_at_station(&self, station: StationType) -> usize {
-        self.agents_at_station.get(&station).map(|v| v.len()).unwrap_or(0)
+    pub fn count_at_station(&self, room: Entity, station: StationType) -> usize {
+        self.agents_at_station.get(&(room, station)).map(|v| v.len()).unwrap_or(0)
     }
 }
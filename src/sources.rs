@@ -0,0 +1,529 @@
+use bevy::log::{info, warn};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::io::{self, BufRead, ErrorKind, Read};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::tail::LogTail;
+
+/// How often the events file is polled when no watcher is available
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Longest line a socket client may send before it is disconnected
+const MAX_LINE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Something an event source produced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceEvent {
+    /// A non-empty line of JSON, with its 1-based line number in the stream
+    Line { text: String, number: u64 },
+    /// The events file was truncated or replaced and reading restarted from the top
+    Rotated,
+    /// Reading failed; the source keeps trying
+    Error(String),
+}
+
+/// A stream of newline-delimited JSON events.
+///
+/// Give `EventReaderPlugin::new` your own sources next to or instead of the
+/// built-in ones. Once opened, a source pushes what it reads to its
+/// `SourceSender` from threads of its own. Those threads should return once
+/// `send` fails or `is_stopped` is true, which happens when the ingestion
+/// thread has gone. A thread blocked on a read, such as on stdin or a socket,
+/// only notices at its next read.
+pub trait EventSource: Send {
+    /// Where the events come from, for log messages
+    fn describe(&self) -> String;
+
+    /// Start reading, sending every line and error to `sender`
+    fn open(&mut self, sender: SourceSender) -> io::Result<()>;
+}
+
+/// Hands what a source read to the ingestion thread, tagged with the source it came from
+#[derive(Clone)]
+pub struct SourceSender {
+    source: usize,
+    sender: Sender<(usize, SourceEvent)>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl SourceSender {
+    /// Send for the source at `source` in the ingestion thread's list, until `stopped` is set
+    pub(crate) fn new(source: usize, sender: Sender<(usize, SourceEvent)>, stopped: Arc<AtomicBool>) -> Self {
+        SourceSender { source, sender, stopped }
+    }
+
+    /// Returns false once the ingestion thread is gone
+    pub fn send(&self, event: SourceEvent) -> bool {
+        !self.is_stopped() && self.sender.send((self.source, event)).is_ok()
+    }
+
+    /// Whether the ingestion thread has gone and the source should stop reading
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+}
+
+/// Environment variable that overrides where the events file is
+pub const EVENTS_PATH_ENV: &str = "CLAUDE_VISUALIZER_EVENTS";
+
+/// Location of the events file written by the hooks, unless overridden by
+/// `CLAUDE_VISUALIZER_EVENTS` or by `configured`, the `events.path` of the
+/// config file for a separate Claude profile
+pub fn default_events_path(configured: Option<&Path>) -> PathBuf {
+    if let Some(path) = std::env::var_os(EVENTS_PATH_ENV).filter(|path| !path.is_empty()) {
+        return PathBuf::from(path);
+    }
+
+    if let Some(path) = configured {
+        return path.to_path_buf();
+    }
+
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".claude-visualizer")
+        .join("events.jsonl")
+}
+
+/// Which event source to open, as given on the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceSpec {
+    /// Tail a JSONL file, starting at its current end
+    File(PathBuf),
+    /// Read from standard input
+    Stdin,
+    /// Listen on a Unix domain socket at this path
+    UnixSocket(PathBuf),
+    /// Listen on this TCP address, which is always a loopback one
+    Tcp(String),
+}
+
+impl FromStr for SourceSpec {
+    type Err = String;
+
+    /// Parse `file:PATH`, `stdin`, `unix:PATH`, `tcp:PORT` or `tcp:HOST:PORT`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "stdin" || s == "-" {
+            return Ok(SourceSpec::Stdin);
+        }
+
+        let Some((kind, rest)) = s.split_once(':') else {
+            return Err(format!(
+                "unknown event source '{}', expected file:PATH, stdin, unix:PATH or tcp:PORT",
+                s
+            ));
+        };
+
+        match kind {
+            "file" => Ok(SourceSpec::File(PathBuf::from(rest))),
+            "unix" => Ok(SourceSpec::UnixSocket(PathBuf::from(rest))),
+            "tcp" => loopback_address(rest).map(SourceSpec::Tcp),
+            _ => Err(format!("unknown event source kind '{}'", kind)),
+        }
+    }
+}
+
+/// The address to listen on for `tcp:PORT` or `tcp:HOST:PORT`.
+///
+/// Anyone who can connect can inject events, so only loopback addresses are allowed.
+fn loopback_address(rest: &str) -> Result<String, String> {
+    // A bare port only listens on localhost
+    if rest.parse::<u16>().is_ok() {
+        return Ok(format!("127.0.0.1:{}", rest));
+    }
+    if let Some(port) = rest.strip_prefix("localhost:").filter(|port| port.parse::<u16>().is_ok()) {
+        return Ok(format!("127.0.0.1:{}", port));
+    }
+
+    match rest.parse::<SocketAddr>() {
+        Ok(address) if address.ip().is_loopback() => Ok(address.to_string()),
+        Ok(address) => Err(format!(
+            "refusing to listen on {}: the tcp source only accepts loopback addresses",
+            address.ip()
+        )),
+        Err(_) => Err(format!("invalid tcp address '{}', expected PORT or HOST:PORT", rest)),
+    }
+}
+
+impl SourceSpec {
+    /// The source to open for this spec
+    pub fn source(&self) -> Box<dyn EventSource> {
+        match self {
+            SourceSpec::File(path) => Box::new(FileSource::new(path.clone())),
+            SourceSpec::Stdin => Box::new(StdinSource),
+            SourceSpec::UnixSocket(path) => Box::new(UnixSocketSource::new(path.clone())),
+            SourceSpec::Tcp(address) => Box::new(TcpSource::new(address.clone())),
+        }
+    }
+}
+
+/// Splits a byte stream into lines, holding back an unterminated tail
+#[derive(Default)]
+pub struct LineBuffer {
+    partial: Vec<u8>,
+    /// Number of lines completed so far
+    line_number: u64,
+}
+
+impl LineBuffer {
+    /// Add received bytes, emitting every line they complete.
+    ///
+    /// Returns false once a line grows past `MAX_LINE_BYTES`, after which the
+    /// rest of the stream should be dropped.
+    pub fn push(&mut self, bytes: &[u8], events: &mut Vec<SourceEvent>) -> bool {
+        for &byte in bytes {
+            if byte == b'\n' {
+                self.line_number += 1;
+                push_line(events, &self.partial, self.line_number);
+                self.partial.clear();
+            } else if self.partial.len() >= MAX_LINE_BYTES {
+                self.partial.clear();
+                return false;
+            } else {
+                self.partial.push(byte);
+            }
+        }
+        true
+    }
+
+    /// Emit whatever is left once the stream has ended
+    pub fn finish(&mut self, events: &mut Vec<SourceEvent>) {
+        let fragment = std::mem::take(&mut self.partial);
+        push_line(events, &fragment, self.line_number + 1);
+    }
+}
+
+/// Emit `bytes` as line `number` unless it is blank
+pub fn push_line(events: &mut Vec<SourceEvent>, bytes: &[u8], number: u64) {
+    let line = String::from_utf8_lossy(bytes);
+    let trimmed = line.trim();
+    if !trimmed.is_empty() {
+        events.push(SourceEvent::Line {
+            text: trimmed.to_string(),
+            number,
+        });
+    }
+}
+
+/// Tails the events file, woken by filesystem notifications
+pub struct FileSource {
+    path: PathBuf,
+    /// Kept alive for as long as the source should receive lines
+    _watcher: Option<RecommendedWatcher>,
+}
+
+impl FileSource {
+    pub fn new(path: PathBuf) -> Self {
+        FileSource { path, _watcher: None }
+    }
+}
+
+impl EventSource for FileSource {
+    fn describe(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    /// Start tailing at the current end of the file, skipping its history
+    fn open(&mut self, sender: SourceSender) -> io::Result<()> {
+        let position = std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+
+        match spawn_watcher(LogTail::new(self.path.clone(), position), sender.clone()) {
+            Ok(watcher) => self._watcher = Some(watcher),
+            Err(err) => {
+                warn!("Could not watch {}, polling instead: {}", self.path.display(), err);
+                spawn_polling_tail(LogTail::new(self.path.clone(), position), sender);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read the tail on a fixed interval, for when notifications are unavailable
+fn spawn_polling_tail(mut tail: LogTail, sender: SourceSender) {
+    std::thread::spawn(move || {
+        while !sender.is_stopped() {
+            for source_event in tail.read_new() {
+                // The receiving side is gone once the app shuts down
+                if !sender.send(source_event) {
+                    return;
+                }
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+/// Watch the directory containing the events file and forward what changed.
+///
+/// The directory is watched rather than the file itself so that the file being
+/// created or renamed into place is noticed too.
+fn spawn_watcher(mut tail: LogTail, sender: SourceSender) -> notify::Result<RecommendedWatcher> {
+    let path = tail.path().to_path_buf();
+    let dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let file_name = path.file_name().map(|name| name.to_os_string());
+
+    std::fs::create_dir_all(&dir)?;
+
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        let Ok(event) = res else {
+            return;
+        };
+
+        // Renames are reported as a kind of modify event
+        if !matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+            return;
+        }
+
+        let touches_events_file = event
+            .paths
+            .iter()
+            .any(|p| p.file_name().map(|name| name.to_os_string()) == file_name);
+        if !touches_events_file {
+            return;
+        }
+
+        for source_event in tail.read_new() {
+            // The receiving side is gone once the app shuts down
+            if !sender.send(source_event) {
+                return;
+            }
+        }
+    })?;
+
+    watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+
+    Ok(watcher)
+}
+
+/// Reads events piped into standard input
+pub struct StdinSource;
+
+impl EventSource for StdinSource {
+    fn describe(&self) -> String {
+        "stdin".to_string()
+    }
+
+    /// Read stdin on its own thread, since it cannot be read without blocking
+    fn open(&mut self, sender: SourceSender) -> io::Result<()> {
+        std::thread::spawn(move || {
+            let stdin = io::stdin();
+            let mut reader = stdin.lock();
+            let mut line = Vec::new();
+            let mut line_number = 0;
+
+            loop {
+                let mut events = Vec::new();
+                match reader.read_until(b'\n', &mut line) {
+                    Ok(0) => break,
+                    Ok(_) => {
+                        line_number += 1;
+                        push_line(&mut events, &line, line_number);
+                        line.clear();
+                    }
+                    Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                    Err(err) => {
+                        sender.send(SourceEvent::Error(format!("stdin: {}", err)));
+                        break;
+                    }
+                }
+
+                for event in events {
+                    // The receiving side is gone once the app shuts down
+                    if !sender.send(event) {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
+
+/// Read a connected client until it disconnects, sending every line it completes
+fn read_connection<S: Read>(mut stream: S, sender: &SourceSender) {
+    let mut buffer = LineBuffer::default();
+    let mut chunk = [0u8; 8192];
+    loop {
+        let mut events = Vec::new();
+        let open = match stream.read(&mut chunk) {
+            Ok(0) => {
+                buffer.finish(&mut events);
+                false
+            }
+            Ok(bytes_read) => {
+                let within_limit = buffer.push(&chunk[..bytes_read], &mut events);
+                if !within_limit {
+                    events.push(SourceEvent::Error(format!(
+                        "connection dropped: line longer than {} bytes",
+                        MAX_LINE_BYTES
+                    )));
+                }
+                within_limit
+            }
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => {
+                buffer.finish(&mut events);
+                events.push(SourceEvent::Error(format!("connection dropped: {}", err)));
+                false
+            }
+        };
+
+        for event in events {
+            // The receiving side is gone once the app shuts down
+            if !sender.send(event) {
+                return;
+            }
+        }
+        if !open {
+            return;
+        }
+    }
+}
+
+/// A listener that socket sources accept clients from
+trait Listener: Send + 'static {
+    type Stream: Read + Send + 'static;
+
+    /// Wait for the next client
+    fn accept_client(&self) -> io::Result<Self::Stream>;
+}
+
+impl Listener for TcpListener {
+    type Stream = TcpStream;
+
+    fn accept_client(&self) -> io::Result<TcpStream> {
+        self.accept().map(|(stream, _)| stream)
+    }
+}
+
+#[cfg(unix)]
+impl Listener for std::os::unix::net::UnixListener {
+    type Stream = std::os::unix::net::UnixStream;
+
+    fn accept_client(&self) -> io::Result<Self::Stream> {
+        self.accept().map(|(stream, _)| stream)
+    }
+}
+
+/// Accept clients on a thread of their own, reading each on its own thread too
+fn spawn_listener<L: Listener>(listener: L, sender: SourceSender) {
+    std::thread::spawn(move || {
+        while !sender.is_stopped() {
+            match listener.accept_client() {
+                Ok(stream) => {
+                    let sender = sender.clone();
+                    std::thread::spawn(move || read_connection(stream, &sender));
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => {
+                    sender.send(SourceEvent::Error(format!("could not accept a connection: {}", err)));
+                    return;
+                }
+            }
+        }
+    });
+}
+
+/// Accepts newline-delimited JSON over TCP
+pub struct TcpSource {
+    address: String,
+}
+
+impl TcpSource {
+    /// Listen on `address`, which should be a loopback one
+    pub fn new(address: String) -> Self {
+        TcpSource { address }
+    }
+}
+
+impl EventSource for TcpSource {
+    fn describe(&self) -> String {
+        format!("tcp://{}", self.address)
+    }
+
+    fn open(&mut self, sender: SourceSender) -> io::Result<()> {
+        let listener = TcpListener::bind(&self.address)?;
+        info!("Listening for events on tcp://{}", self.address);
+        spawn_listener(listener, sender);
+        Ok(())
+    }
+}
+
+/// Accepts newline-delimited JSON over a Unix domain socket
+pub struct UnixSocketSource {
+    path: PathBuf,
+    /// Device and inode of the socket this source bound, the only file it may remove
+    bound: Option<(u64, u64)>,
+}
+
+impl UnixSocketSource {
+    pub fn new(path: PathBuf) -> Self {
+        UnixSocketSource { path, bound: None }
+    }
+}
+
+impl EventSource for UnixSocketSource {
+    fn describe(&self) -> String {
+        format!("unix://{}", self.path.display())
+    }
+
+    #[cfg(unix)]
+    fn open(&mut self, sender: SourceSender) -> io::Result<()> {
+        use std::os::unix::fs::{FileTypeExt, MetadataExt};
+
+        // A socket left behind by an earlier run would make bind fail, but
+        // anything else at the path is not ours to delete
+        match std::fs::symlink_metadata(&self.path) {
+            Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(&self.path)?,
+            Ok(_) => {
+                return Err(io::Error::new(
+                    ErrorKind::AlreadyExists,
+                    format!("{} exists and is not a socket", self.path.display()),
+                ));
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let listener = std::os::unix::net::UnixListener::bind(&self.path)?;
+        let metadata = std::fs::symlink_metadata(&self.path)?;
+        self.bound = Some((metadata.dev(), metadata.ino()));
+        info!("Listening for events on {}", self.path.display());
+        spawn_listener(listener, sender);
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn open(&mut self, _sender: SourceSender) -> io::Result<()> {
+        Err(io::Error::new(
+            ErrorKind::Unsupported,
+            "Unix domain sockets are not available on this platform",
+        ))
+    }
+}
+
+#[cfg(unix)]
+impl Drop for UnixSocketSource {
+    /// Remove the socket, unless something else has taken its place since
+    fn drop(&mut self) {
+        use std::os::unix::fs::MetadataExt;
+
+        let Some(bound) = self.bound else {
+            return;
+        };
+        let still_ours = std::fs::symlink_metadata(&self.path)
+            .is_ok_and(|metadata| (metadata.dev(), metadata.ino()) == bound);
+        if still_ours {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
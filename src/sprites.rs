@@ -2,7 +2,10 @@ use bevy::prelude::*;
 use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
 use rand::seq::SliceRandom;
 
-use crate::components::{AgentType, StationType};
+use std::collections::HashMap;
+
+use crate::agent_types::{stable_hash, AgentType, AgentTypes};
+use crate::components::StationType;
 
 /// Type alias for character drawing functions
 type DrawFn = fn(&mut [u8], u32);
@@ -47,8 +50,36 @@ const CHARACTER_POOL: &[(DrawFn, &str)] = &[
 /// Resource holding generated sprite handles
 #[derive(Resource, Default)]
 pub struct SpriteAssets {
-    pub agents: std::collections::HashMap<AgentType, Handle<Image>>,
-    pub stations: std::collections::HashMap<StationType, Handle<Image>>,
+    /// Characters given to the main agent and the built-in types for this run, by type id
+    pub agents: HashMap<String, Handle<Image>>,
+    /// Every character, by name, in pool order
+    pub characters: Vec<(String, Handle<Image>)>,
+    pub stations: HashMap<StationType, Handle<Image>>,
+}
+
+impl SpriteAssets {
+    /// Sprite for an agent: the character its type names, the one it was given
+    /// this run, or else one picked by its name so it is the same every run
+    pub fn agent(&self, agent_type: &AgentType) -> Option<Handle<Image>> {
+        // Names are checked when the config is loaded
+        let named = agent_type
+            .sprite()
+            .and_then(|sprite| self.characters.iter().find(|(name, _)| name.eq_ignore_ascii_case(sprite)));
+        if let Some((_, handle)) = named {
+            return Some(handle.clone());
+        }
+        if let Some(handle) = self.agents.get(agent_type.id()) {
+            return Some(handle.clone());
+        }
+
+        let index = stable_hash(agent_type.id()) as usize % self.characters.len().max(1);
+        self.characters.get(index).map(|(_, handle)| handle.clone())
+    }
+}
+
+/// Names of the characters that can be drawn, for `sprite` in the config
+pub fn character_names() -> impl Iterator<Item = &'static str> {
+    CHARACTER_POOL.iter().map(|(_, name)| *name)
 }
 
 /// Generate all sprite assets with randomized character assignments
@@ -56,47 +87,31 @@ pub fn generate_sprites(
     mut images: ResMut<Assets<Image>>,
     mut sprite_assets: ResMut<SpriteAssets>,
 ) {
-    // Shuffle character assignments for this session
-    let mut rng = rand::thread_rng();
-    let mut characters: Vec<(DrawFn, &str)> = CHARACTER_POOL.to_vec();
-    characters.shuffle(&mut rng);
-
-    // Agent types to assign characters to
-    let agent_types = [
-        AgentType::Main,
-        AgentType::Explore,
-        AgentType::Plan,
-        AgentType::Bash,
-        AgentType::CodeReviewer,
-        AgentType::UIUXReviewer,
-        AgentType::StatuslineSetup,
-        AgentType::ClaudeCodeGuide,
-        AgentType::Haiku,
-        AgentType::DevopsEngineer,
-        AgentType::SecurityAnalyst,
-        AgentType::ProjectManager,
-        AgentType::General,
-    ];
-
-    // Log the random assignments for this session
-    println!("\n🎲 Random character assignments for this session:");
-    println!("================================================");
-
-    // Generate agent sprites with shuffled characters
-    for (i, agent_type) in agent_types.iter().enumerate() {
-        let (draw_fn, char_name) = characters[i % characters.len()];
-
-        println!("  {:?} → {}", agent_type, char_name);
-
+    for (draw_fn, name) in CHARACTER_POOL {
         let size = 32u32;
         let mut pixels = vec![0u8; (size * size * 4) as usize];
         draw_fn(&mut pixels, size);
         let image = create_image(size, pixels);
+        sprite_assets.characters.push((name.to_string(), images.add(image)));
+    }
 
-        let handle = images.add(image);
-        sprite_assets.agents.insert(*agent_type, handle);
+    // Shuffle character assignments for this session
+    let mut rng = rand::thread_rng();
+    let mut characters = sprite_assets.characters.clone();
+    characters.shuffle(&mut rng);
+
+    // Agent types to assign characters to; configured and unknown ones get theirs by name
+    let agent_types = std::iter::once(AgentType::main().id().to_string())
+        .chain(AgentTypes::builtin_ids().map(str::to_string));
+
+    let mut assignments = Vec::new();
+    for (i, agent_type) in agent_types.enumerate() {
+        let (char_name, handle) = characters[i % characters.len()].clone();
+        assignments.push(format!("{} → {}", agent_type, char_name));
+        sprite_assets.agents.insert(agent_type, handle);
     }
-    println!("================================================\n");
+    // Logged rather than printed, since a host app may use stdout for itself
+    info!("Random character assignments for this session: {}", assignments.join(", "));
 
     // Generate station sprites (these stay fixed)
     for station_type in [
@@ -3057,6 +3072,6 @@ pub struct SpriteGenPlugin;
 impl Plugin for SpriteGenPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<SpriteAssets>()
-            .add_systems(Startup, generate_sprites.before(super::setup_workspace));
+            .add_systems(Startup, generate_sprites);
     }
 }
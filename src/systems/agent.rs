@@ -1,257 +1,396 @@
 use bevy::prelude::*;
 
 use crate::components::*;
-use crate::events::EventType;
-use crate::resources::{EventQueue, GameState, StationOccupancy, StationPositions};
+use crate::domain::{AgentId, Change, FinishReason};
+use crate::resources::{EventQueue, GameState, Office, Rooms, StationOccupancy, StationPositions, Timeline};
 use crate::sprites::SpriteAssets;
+use crate::systems::effects::{HookSignal, Signal};
+use crate::systems::workspace::{room_label, spawn_room};
 
 /// Marker for the main agent
 #[derive(Component)]
 pub struct MainAgent;
 
-/// System to spawn the main agent at startup
+/// Spawn the main agent of a room in the middle of it
 pub fn spawn_main_agent(
-    mut commands: Commands,
-    mut game_state: ResMut<GameState>,
-    mut station_occupancy: ResMut<StationOccupancy>,
-    station_positions: Res<StationPositions>,
-    sprite_assets: Res<SpriteAssets>,
-) {
+    commands: &mut Commands,
+    station_occupancy: &mut StationOccupancy,
+    station_positions: &StationPositions,
+    sprite_assets: &SpriteAssets,
+    room: Entity,
+) -> Entity {
     let center = station_positions.center;
 
     // Get sprite for main agent
-    if let Some(image_handle) = sprite_assets.agents.get(&AgentType::Main) {
-        let entity = commands
-            .spawn((
+    let image_handle = sprite_assets.agent(&AgentType::main()).unwrap_or_default();
+    let entity = commands
+        .spawn((
+            Sprite {
+                image: image_handle,
+                custom_size: Some(Vec2::new(48.0, 48.0)), // Scale up 1.5x
+                ..default()
+            },
+            Transform::from_xyz(center.x, center.y, 10.0),
+            Agent {
+                id: "main".to_string(),
+                agent_type: AgentType::main(),
+                tool_use_id: None,
+            },
+            MainAgent,
+            CurrentStation { station: Some(StationType::Center) },
+            LabelStagger { index: 0 },
+            Speed::default(),
+            AnimationController::default(),
+        ))
+        .with_children(|parent| {
+            // Name label above character
+            parent.spawn((
                 Sprite {
-                    image: image_handle.clone(),
-                    custom_size: Some(Vec2::new(48.0, 48.0)), // Scale up 1.5x
+                    color: Color::srgba(0.2, 0.5, 0.9, 0.8),
+                    custom_size: Some(Vec2::new(40.0, 16.0)),
                     ..default()
                 },
-                Transform::from_xyz(center.x, center.y, 10.0),
-                Agent {
-                    id: "main".to_string(),
-                    agent_type: AgentType::Main,
-                    tool_use_id: None,
+                Transform::from_xyz(0.0, 32.0, 0.5),
+                AgentLabel,
+            ));
+            parent.spawn((
+                Text2d::new("Main"),
+                TextFont {
+                    font_size: 11.0,
+                    ..default()
                 },
-                MainAgent,
-                CurrentStation { station: Some(StationType::Center) },
-                LabelStagger { index: 0 },
-                Speed::default(),
-                AnimationController::default(),
-            ))
-            .with_children(|parent| {
-                // Name label above character
-                parent.spawn((
-                    Sprite {
-                        color: Color::srgba(0.2, 0.5, 0.9, 0.8),
-                        custom_size: Some(Vec2::new(40.0, 16.0)),
-                        ..default()
-                    },
-                    Transform::from_xyz(0.0, 32.0, 0.5),
-                    AgentLabel,
-                ));
-                parent.spawn((
-                    Text2d::new("Main"),
-                    TextFont {
-                        font_size: 11.0,
-                        ..default()
-                    },
-                    TextColor(Color::WHITE),
-                    Transform::from_xyz(0.0, 32.0, 1.0),
-                    AgentLabel,
-                ));
-            })
-            .id();
+                TextColor(Color::WHITE),
+                Transform::from_xyz(0.0, 32.0, 1.0),
+                AgentLabel,
+            ));
+        })
+        .set_parent(room)
+        .id();
 
-        // Register with station occupancy
-        station_occupancy.add_agent(StationType::Center, entity);
-    }
+    // Register with station occupancy
+    station_occupancy.add_agent(room, StationType::Center, entity);
+    entity
+}
+
+/// Spawn a subagent at `station`, started by `parent`, and register it there.
+///
+/// Returns `None` if there is no sprite for the agent type.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_subagent(
+    commands: &mut Commands,
+    station_occupancy: &mut StationOccupancy,
+    station_positions: &StationPositions,
+    sprite_assets: &SpriteAssets,
+    room: Entity,
+    agent_type: AgentType,
+    tool_use_id: &str,
+    station: StationType,
+    parent: Entity,
+) -> Option<Entity> {
+    let position = station_positions.get(station);
+
+    // Calculate stagger index for labels
+    let stagger_index = station_occupancy.count_at_station(room, station);
+
+    // Get sprite for this agent type
+    let image_handle = sprite_assets.agent(&agent_type)?;
 
-    game_state.agent_count = 1;
+    // Name label - staggered vertically based on index
+    let label_color = agent_type.color();
+    let label = agent_type.label().to_string();
+
+    // Spawn at the station directly
+    let entity = commands
+        .spawn((
+            Sprite {
+                image: image_handle.clone(),
+                custom_size: Some(Vec2::new(48.0, 48.0)),
+                ..default()
+            },
+            Transform::from_xyz(position.x, position.y, 10.0),
+            Agent {
+                id: tool_use_id.to_string(),
+                agent_type,
+                tool_use_id: Some(tool_use_id.to_string()),
+            },
+            CurrentStation { station: Some(station) },
+            LabelStagger { index: stagger_index },
+            Speed::SUBAGENT,
+            AnimationController::default(),
+            SpawnedBy(parent),
+        ))
+        .with_children(|parent| {
+            let label_y_offset = 28.0 + (stagger_index as f32 * 16.0);
+
+            parent.spawn((
+                Sprite {
+                    color: Color::srgba(
+                        label_color.to_srgba().red * 0.7,
+                        label_color.to_srgba().green * 0.7,
+                        label_color.to_srgba().blue * 0.7,
+                        0.9,
+                    ),
+                    custom_size: Some(Vec2::new(50.0, 14.0)),
+                    ..default()
+                },
+                Transform::from_xyz(0.0, label_y_offset, 0.5),
+                AgentLabel,
+            ));
+            parent.spawn((
+                Text2d::new(label),
+                TextFont {
+                    font_size: 10.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                Transform::from_xyz(0.0, label_y_offset, 1.0),
+                AgentLabel,
+            ));
+        })
+        .set_parent(room)
+        .id();
+
+    // Register with station occupancy
+    station_occupancy.add_agent(room, station, entity);
+    Some(entity)
 }
 
 /// System to process events and spawn/move agents
+#[allow(clippy::too_many_arguments)]
 pub fn process_events_system(
     mut commands: Commands,
+    time: Res<Time>,
     mut event_queue: ResMut<EventQueue>,
+    mut timeline: ResMut<Timeline>,
+    mut office: ResMut<Office>,
+    mut rooms: ResMut<Rooms>,
     mut game_state: ResMut<GameState>,
     mut station_occupancy: ResMut<StationOccupancy>,
     station_positions: Res<StationPositions>,
     sprite_assets: Res<SpriteAssets>,
-    mut agents: Query<(Entity, &Agent, &mut Transform, Option<&mut CurrentStation>), Without<MainAgent>>,
-    mut main_agent: Query<(Entity, &mut Transform, &mut CurrentStation), With<MainAgent>>,
+    agents: Query<(Entity, &Agent)>,
+    mut hook_signals: EventWriter<HookSignal>,
 ) {
     // Process only a few events per frame to allow rendering between spawn/despawn
     let mut events_processed = 0;
-    const MAX_EVENTS_PER_FRAME: usize = 3;
+    let budget = timeline.take_budget();
+
+    // Subagents spawned this frame, which the query cannot see yet
+    let mut spawned: Vec<(String, Entity)> = Vec::new();
 
     while let Some(event) = event_queue.pop() {
         events_processed += 1;
-        if events_processed > MAX_EVENTS_PER_FRAME {
+        if events_processed > budget {
             // Re-queue the event for next frame
             event_queue.events.push_front(event);
             break;
         }
-        match event.event_type {
-            EventType::PreToolUse => {
-                let station_type = StationType::for_tool(&event.tool_name);
-                let base_pos = station_positions.get(station_type);
-
-                // If it's a Task tool, spawn a new subagent
-                if event.tool_name == "Task" {
-                    if let Some(subagent_type_str) = event.get_subagent_type() {
-                        let agent_type = AgentType::from_str(&subagent_type_str);
-
-                        // Spawn at the agent's home station (based on agent type)
-                        let home_station = agent_type.home_station();
-                        let home_pos = station_positions.get(home_station);
-
-                        // Calculate stagger index for labels
-                        let stagger_index = station_occupancy.count_at_station(home_station);
-
-                        // Get sprite for this agent type
-                        if let Some(image_handle) = sprite_assets.agents.get(&agent_type) {
-                            // Spawn at home station directly
-                            let entity = commands
-                                .spawn((
-                                    Sprite {
-                                        image: image_handle.clone(),
-                                        custom_size: Some(Vec2::new(48.0, 48.0)),
-                                        ..default()
-                                    },
-                                    Transform::from_xyz(home_pos.x, home_pos.y, 10.0),
-                                    Agent {
-                                        id: event.tool_use_id.clone(),
-                                        agent_type,
-                                        tool_use_id: Some(event.tool_use_id.clone()),
-                                    },
-                                    CurrentStation { station: Some(home_station) },
-                                    LabelStagger { index: stagger_index },
-                                    Speed(180.0),
-                                    AnimationController::default(),
-                                ))
-                                .with_children(|parent| {
-                                    // Name label - staggered vertically based on index
-                                    let label_color = agent_type.color();
-                                    let label_y_offset = 28.0 + (stagger_index as f32 * 16.0);
-
-                                    parent.spawn((
-                                        Sprite {
-                                            color: Color::srgba(
-                                                label_color.to_srgba().red * 0.7,
-                                                label_color.to_srgba().green * 0.7,
-                                                label_color.to_srgba().blue * 0.7,
-                                                0.9,
-                                            ),
-                                            custom_size: Some(Vec2::new(50.0, 14.0)),
-                                            ..default()
-                                        },
-                                        Transform::from_xyz(0.0, label_y_offset, 0.5),
-                                        AgentLabel,
-                                    ));
-                                    parent.spawn((
-                                        Text2d::new(agent_type.label()),
-                                        TextFont {
-                                            font_size: 10.0,
-                                            ..default()
-                                        },
-                                        TextColor(Color::WHITE),
-                                        Transform::from_xyz(0.0, label_y_offset, 1.0),
-                                        AgentLabel,
-                                    ));
-                                })
-                                .id();
-
-                            // Register with station occupancy
-                            station_occupancy.add_agent(home_station, entity);
-                            game_state.agent_count += 1;
-                        }
-                    }
-                } else {
-                    // Move main agent to the appropriate station
-                    if let Ok((entity, _transform, mut current_station)) = main_agent.get_single_mut() {
-                        // Update station occupancy
-                        if current_station.station.is_some() {
-                            station_occupancy.remove_agent(entity);
-                        }
-                        station_occupancy.add_agent(station_type, entity);
-                        current_station.station = Some(station_type);
+        timeline.record(&event);
+
+        let changes = office.0.apply(&event);
+        let Some(state) = office.0.get(&event.session_id) else {
+            continue;
+        };
 
-                        // Calculate orbital offset
-                        let orbital_offset = station_occupancy.get_orbital_offset(station_type, entity);
-                        let target_pos = base_pos + orbital_offset;
+        // A new session takes the empty room if there is one, or gets its own
+        if rooms.for_session(&event.session_id).is_none() {
+            let label = room_label(&event.session_id, state.cwd.as_deref());
+            if let Some(room) = rooms.unclaimed_mut() {
+                room.session_id = Some(event.session_id.clone());
+                commands.entity(room.label).insert(Text2d::new(label));
+            } else {
+                let room = spawn_room(
+                    &mut commands,
+                    &mut station_occupancy,
+                    &station_positions,
+                    &sprite_assets,
+                    Some(event.session_id.clone()),
+                    &label,
+                );
+                rooms.rooms.push(room);
+            }
+        }
+        let Some(room) = rooms.for_session_mut(&event.session_id) else {
+            continue;
+        };
+        room.last_activity = time.elapsed_secs();
+        room.open = state.office_open;
+        let (room, main_agent) = (room.entity, room.main_agent);
 
-                        commands.entity(entity).insert(MovementTarget {
-                            position: target_pos,
-                            station_type: Some(station_type),
-                        });
+        for change in changes {
+            let signal = |kind| HookSignal {
+                agent: main_agent,
+                kind,
+            };
+            match change {
+                Change::SubagentSpawned {
+                    tool_use_id,
+                    agent_type,
+                    station,
+                    parent,
+                } => {
+                    let parent = match &parent {
+                        AgentId::Main => Some(main_agent),
+                        AgentId::Subagent(parent) => find_subagent(&agents, &spawned, parent),
+                    };
+                    let entity = spawn_subagent(
+                        &mut commands,
+                        &mut station_occupancy,
+                        &station_positions,
+                        &sprite_assets,
+                        room,
+                        agent_type,
+                        &tool_use_id,
+                        station,
+                        parent.unwrap_or(main_agent),
+                    );
+                    if let Some(entity) = entity {
+                        spawned.push((tool_use_id, entity));
                     }
                 }
-            }
-            EventType::PostToolUse => {
-                // Tool completed - despawn the subagent
-                if event.tool_name == "Task" {
-                    for (entity, agent, _transform, _) in agents.iter_mut() {
-                        if agent.tool_use_id.as_ref() == Some(&event.tool_use_id) {
-                            station_occupancy.remove_agent(entity);
+                Change::SubagentFinished { tool_use_id, reason } => {
+                    if let Some(entity) = find_subagent(&agents, &spawned, &tool_use_id) {
+                        station_occupancy.remove_agent(entity);
+                        spawned.retain(|(_, e)| *e != entity);
+                        if reason == FinishReason::ParentFinished {
+                            orphan(&mut commands, entity);
+                        } else {
                             commands.entity(entity).despawn_recursive();
-                            game_state.agent_count = game_state.agent_count.saturating_sub(1);
-                            break;
                         }
                     }
                 }
+                Change::AgentMoved { agent, station } => {
+                    let entity = match &agent {
+                        AgentId::Main => main_agent,
+                        AgentId::Subagent(tool_use_id) => {
+                            let Some(entity) = find_subagent(&agents, &spawned, tool_use_id) else {
+                                continue;
+                            };
+                            entity
+                        }
+                    };
+                    station_occupancy.add_agent(room, station, entity);
+
+                    let orbital_offset = station_occupancy.get_orbital_offset(room, station, entity);
+                    commands.entity(entity).insert((
+                        CurrentStation { station: Some(station) },
+                        MovementTarget {
+                            position: station_positions.get(station) + orbital_offset,
+                            station_type: Some(station),
+                        },
+                    ));
+                }
+                Change::ToolStarted { .. } | Change::ToolFinished { .. } => {
+                    hook_signals.send(signal(Signal::ToolActivity));
+                }
+                Change::PromptSubmitted(prompt) => {
+                    hook_signals.send(signal(Signal::PromptSubmitted(prompt)));
+                }
+                Change::TurnEnded => {
+                    hook_signals.send(signal(Signal::TurnEnded));
+                }
+                Change::AttentionNeeded(message) => {
+                    hook_signals.send(signal(Signal::AttentionNeeded(message)));
+                }
+                Change::SessionStarted => {
+                    hook_signals.send(signal(Signal::SessionStarted));
+                }
+                Change::SessionEnded => {
+                    hook_signals.send(signal(Signal::SessionEnded));
+                }
+                Change::Compacting => {
+                    hook_signals.send(signal(Signal::Compacting));
+                }
+                Change::SessionSwitched { .. } => {
+                    // The session's room was opened above
+                }
             }
-            EventType::SubagentStop => {
-                // Already handled by PostToolUse for Task
-            }
-            EventType::Unknown => {}
         }
+
+        game_state.agent_count = office.0.agent_count();
     }
 }
 
-/// System to clear all subagents when session changes
-pub fn handle_session_change_system(
+/// How long a subagent left behind by its parent takes to fade out
+const ORPHAN_FADE_SECS: f32 = 2.0;
+
+/// Leave a subagent whose parent finished where it stands, marked as such
+fn orphan(commands: &mut Commands, entity: Entity) {
+    commands
+        .entity(entity)
+        .remove::<MovementTarget>()
+        .insert(Orphaned {
+            timer: Timer::from_seconds(ORPHAN_FADE_SECS, TimerMode::Once),
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                Text2d::new("parent finished"),
+                TextFont {
+                    font_size: 10.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.5, 0.4)),
+                Transform::from_xyz(0.0, -34.0, 1.0),
+            ));
+        });
+}
+
+/// System to fade out orphaned subagents, then despawn them
+pub fn orphaned_agents_system(
     mut commands: Commands,
-    mut game_state: ResMut<GameState>,
-    mut station_occupancy: ResMut<StationOccupancy>,
-    agents: Query<Entity, (With<Agent>, Without<MainAgent>)>,
+    time: Res<Time>,
+    mut orphans: Query<(Entity, &mut Orphaned, &mut Sprite)>,
 ) {
-    if !game_state.session_changed {
-        return;
+    for (entity, mut orphaned, mut sprite) in orphans.iter_mut() {
+        orphaned.timer.tick(time.delta());
+        if orphaned.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        // Reddened, and fading
+        sprite.color = Color::srgba(1.0, 0.55, 0.5, orphaned.timer.fraction_remaining());
     }
+}
 
-    // Clear the flag
-    game_state.session_changed = false;
-
-    // Despawn all subagents
-    let mut count = 0;
-    for entity in agents.iter() {
-        station_occupancy.remove_agent(entity);
-        commands.entity(entity).despawn_recursive();
-        count += 1;
+/// The entity drawing a subagent, including those spawned earlier this frame
+fn find_subagent(
+    agents: &Query<(Entity, &Agent)>,
+    spawned: &[(String, Entity)],
+    tool_use_id: &str,
+) -> Option<Entity> {
+    if let Some((_, entity)) = spawned.iter().find(|(id, _)| id == tool_use_id) {
+        return Some(*entity);
     }
 
-    if count > 0 {
-        game_state.agent_count = 1; // Just main agent remains
-    }
+    agents
+        .iter()
+        .find(|(_, agent)| agent.tool_use_id.as_deref() == Some(tool_use_id))
+        .map(|(entity, _)| entity)
 }
 
+/// Subagents with the room they are in, where they stand and where they are heading
+type OrbitingAgents<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Parent, &'static CurrentStation, &'static mut MovementTarget),
+    (With<Agent>, Without<MainAgent>),
+>;
+
 /// System to update orbital positions when multiple agents are at the same station
 pub fn update_orbital_positions_system(
     station_occupancy: Res<StationOccupancy>,
     station_positions: Res<StationPositions>,
-    mut agents: Query<(Entity, &CurrentStation, &mut MovementTarget), (With<Agent>, Without<MainAgent>)>,
+    mut agents: OrbitingAgents,
 ) {
     // Only run if station occupancy changed
     if !station_occupancy.is_changed() {
         return;
     }
 
-    for (entity, current_station, mut target) in agents.iter_mut() {
+    for (entity, room, current_station, mut target) in agents.iter_mut() {
         if let Some(station) = current_station.station {
             let base_pos = station_positions.get(station);
-            let orbital_offset = station_occupancy.get_orbital_offset(station, entity);
+            let orbital_offset = station_occupancy.get_orbital_offset(room.get(), station, entity);
             target.position = base_pos + orbital_offset;
         }
     }
@@ -264,14 +403,289 @@ impl Plugin for AgentPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<StationPositions>()
             .init_resource::<StationOccupancy>()
-            .add_systems(
-                Startup,
-                spawn_main_agent.after(crate::sprites::generate_sprites),
-            )
+            .init_resource::<Timeline>()
+            .init_resource::<Office>()
+            .init_resource::<Rooms>()
+            .add_event::<HookSignal>()
             .add_systems(Update, (
-                handle_session_change_system,
-                process_events_system.after(handle_session_change_system),
+                process_events_system,
                 update_orbital_positions_system.after(process_events_system),
+                orphaned_agents_system.after(process_events_system),
             ));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::events::{EventType, ToolEvent};
+    use crate::harness::{event, task, tool, HeadlessApp, TICK};
+
+    const SESSION: &str = "session-a";
+
+    #[test]
+    fn main_agent_walks_to_the_station_for_its_tool() {
+        let mut sim = HeadlessApp::new();
+        let library = StationPositions::default().library;
+
+        sim.send(tool(SESSION, EventType::PreToolUse, "Read", "toolu_1"));
+        sim.tick(1);
+        assert_eq!(sim.main_agent_station(), Some(StationType::Library));
+        assert_eq!(sim.occupancy(StationType::Library), 1);
+        assert_eq!(sim.occupancy(StationType::Center), 0);
+
+        // 270px at 150px/s is under two seconds
+        sim.tick(150);
+        assert!(sim.main_agent_position().distance(library) < 5.0);
+    }
+
+    #[test]
+    fn task_spawns_a_subagent_at_its_home_station_until_it_finishes() {
+        let mut sim = HeadlessApp::new();
+
+        sim.send(task(SESSION, EventType::PreToolUse, "toolu_task", "Explore"));
+        sim.tick(2);
+        assert_eq!(sim.agent_count(), 2);
+        assert_eq!(sim.game_state().agent_count, 2);
+
+        let subagents = sim.subagents();
+        assert_eq!(subagents.len(), 1);
+        let (id, agent_type, position) = &subagents[0];
+        assert_eq!(id, "toolu_task");
+        assert_eq!(agent_type.id(), "explore");
+        assert_eq!(*position, StationPositions::default().library);
+        assert_eq!(sim.occupancy(StationType::Library), 1);
+
+        // The main agent stays put while the subagent works
+        assert_eq!(sim.main_agent_station(), Some(StationType::Center));
+
+        sim.send(task(SESSION, EventType::PostToolUse, "toolu_task", "Explore"));
+        sim.tick(2);
+        assert_eq!(sim.agent_count(), 1);
+        assert_eq!(sim.game_state().agent_count, 1);
+        assert_eq!(sim.occupancy(StationType::Library), 0);
+    }
+
+    #[test]
+    fn a_subagent_walks_to_the_station_for_its_own_tool() {
+        let mut sim = HeadlessApp::new();
+        let terminal = StationPositions::default().terminal;
+
+        sim.send(task(SESSION, EventType::PreToolUse, "toolu_task", "Explore"));
+        sim.send(tool(SESSION, EventType::PreToolUse, "Bash", "toolu_bash"));
+        sim.tick(200);
+
+        // The main agent waits for its Task while the subagent does the work
+        assert_eq!(sim.main_agent_station(), Some(StationType::Center));
+        assert_eq!(sim.occupancy(StationType::Terminal), 1);
+        let (_, _, position) = &sim.subagents()[0];
+        assert!(position.distance(terminal) < 5.0);
+    }
+
+    #[test]
+    fn subagents_left_behind_by_their_parent_fade_out() {
+        let mut sim = HeadlessApp::new();
+        sim.send(task(SESSION, EventType::PreToolUse, "toolu_parent", "general-purpose"));
+        sim.send(ToolEvent {
+            parent_tool_use_id: Some("toolu_parent".to_string()),
+            ..task(SESSION, EventType::PreToolUse, "toolu_child", "Explore")
+        });
+        sim.tick(1);
+
+        let world = sim.app.world_mut();
+        let links: Vec<(Option<String>, Option<String>)> = world
+            .query::<(&Agent, &SpawnedBy)>()
+            .iter(world)
+            .map(|(agent, spawned_by)| {
+                let parent = world.get::<Agent>(spawned_by.0).unwrap();
+                (agent.tool_use_id.clone(), parent.tool_use_id.clone())
+            })
+            .collect();
+        assert!(links.contains(&(Some("toolu_child".to_string()), Some("toolu_parent".to_string()))));
+        assert!(links.contains(&(Some("toolu_parent".to_string()), None)));
+
+        sim.send(task(SESSION, EventType::PostToolUse, "toolu_parent", "general-purpose"));
+        sim.tick(1);
+        // The child stays a moment to show it was cut off
+        assert_eq!(sim.agent_count(), 2);
+        assert_eq!(sim.game_state().agent_count, 1);
+        assert_eq!(sim.occupancy(StationType::Library), 0);
+
+        sim.tick((ORPHAN_FADE_SECS / TICK.as_secs_f32()) as usize + 2);
+        assert_eq!(sim.agent_count(), 1);
+    }
+
+    #[test]
+    fn agents_sharing_a_station_orbit_it() {
+        let mut sim = HeadlessApp::new();
+        let library = StationPositions::default().library;
+
+        sim.send(task(SESSION, EventType::PreToolUse, "toolu_a", "Explore"));
+        sim.send(task(SESSION, EventType::PreToolUse, "toolu_b", "code-reviewer"));
+        // The transcript shows the main agent searching alongside them
+        sim.send(ToolEvent {
+            parent_tool_use_id: Some(String::new()),
+            ..tool(SESSION, EventType::PreToolUse, "Grep", "toolu_grep")
+        });
+        sim.tick(200);
+
+        assert_eq!(sim.occupancy(StationType::Library), 3);
+        let main = sim.main_agent_position();
+        assert!((main.distance(library) - 45.0).abs() < 5.0);
+        for (_, _, position) in sim.subagents() {
+            assert!(position.distance(main) > 10.0);
+        }
+    }
+
+    #[test]
+    fn events_are_processed_a_few_per_frame() {
+        let mut sim = HeadlessApp::new();
+
+        for index in 0..7 {
+            let id = format!("toolu_{}", index);
+            sim.send(task(SESSION, EventType::PreToolUse, &id, "Plan"));
+        }
+        sim.tick(1);
+        assert_eq!(sim.agent_count(), 4);
+        sim.tick(2);
+        assert_eq!(sim.agent_count(), 8);
+    }
+
+    #[test]
+    fn the_history_keeps_the_latest_events_and_seeks_from_what_it_dropped() {
+        let mut sim = HeadlessApp::new();
+        sim.app.world_mut().resource_mut::<Timeline>().history_limit = 2;
+
+        sim.send(task(SESSION, EventType::PreToolUse, "toolu_task", "Plan"));
+        let mut read = tool(SESSION, EventType::PostToolUse, "Read", "toolu_read");
+        read.tool_response = Some(serde_json::json!({ "file": "x".repeat(1024) }));
+        sim.send(read);
+        sim.send(tool(SESSION, EventType::PreToolUse, "Grep", "toolu_grep"));
+        sim.tick(2);
+
+        let timeline = sim.app.world().resource::<Timeline>();
+        assert_eq!(timeline.position(), 2);
+        assert!(timeline.history.iter().all(|event| event.tool_response.is_none()));
+        // The Task was dropped from the history but its subagent is still there
+        let sessions = timeline.replay(Default::default());
+        assert_eq!(sessions.get(SESSION).unwrap().subagents.len(), 1);
+    }
+
+    #[test]
+    fn duplicate_task_events_spawn_one_subagent() {
+        let mut sim = HeadlessApp::new();
+
+        sim.send(task(SESSION, EventType::PreToolUse, "toolu_task", "Plan"));
+        sim.send(task(SESSION, EventType::PreToolUse, "toolu_task", "Plan"));
+        sim.tick(3);
+        assert_eq!(sim.agent_count(), 2);
+    }
+
+    #[test]
+    fn a_second_session_gets_its_own_room() {
+        let mut sim = HeadlessApp::new();
+
+        sim.send(task(SESSION, EventType::PreToolUse, "toolu_task", "Bash"));
+        sim.tick(2);
+        assert_eq!(sim.agent_count(), 2);
+        assert_eq!(sim.rooms(), vec![Some(SESSION.to_string())]);
+
+        sim.send(tool("session-b", EventType::PreToolUse, "Edit", "toolu_edit"));
+        sim.tick(2);
+        assert_eq!(sim.rooms(), vec![Some(SESSION.to_string()), Some("session-b".to_string())]);
+        // Each room has a main agent, and the first session keeps its subagent
+        assert_eq!(sim.agent_count(), 3);
+        assert_eq!(sim.game_state().agent_count, 3);
+        assert_eq!(sim.game_state().session_id.as_deref(), Some("session-b"));
+        assert_eq!(sim.occupancy(StationType::Terminal), 1);
+        assert_eq!(sim.occupancy(StationType::Desk), 1);
+        assert_eq!(sim.main_agent_station(), Some(StationType::Center));
+    }
+
+    #[test]
+    fn idle_rooms_fade_out_and_the_office_is_never_empty() {
+        let mut sim = HeadlessApp::new();
+        sim.app.world_mut().resource_mut::<Config>().sessions.idle_timeout = 1.0;
+
+        sim.send(task(SESSION, EventType::PreToolUse, "toolu_task", "Plan"));
+        sim.tick(30);
+        sim.send(event("session-b", EventType::UserPromptSubmit));
+        sim.tick(50);
+        // Past the timeout for the first session only, so it is fading
+        assert_eq!(sim.rooms().len(), 2);
+        let rooms = sim.app.world().resource::<Rooms>();
+        assert!(rooms.rooms[0].fading);
+        assert!(!rooms.rooms[1].fading);
+
+        // Both gone after the fade, leaving an empty room behind
+        sim.tick(300);
+        assert_eq!(sim.rooms(), vec![None]);
+        assert_eq!(sim.agent_count(), 1);
+        assert_eq!(sim.app.world().resource::<Office>().0.agent_count(), 0);
+
+        // A returning session moves back in
+        sim.send(event(SESSION, EventType::UserPromptSubmit));
+        sim.tick(1);
+        assert_eq!(sim.rooms(), vec![Some(SESSION.to_string())]);
+        assert_eq!(sim.agent_count(), 1);
+    }
+
+    #[test]
+    fn seeking_back_does_not_reopen_rooms_that_faded_out() {
+        let mut sim = HeadlessApp::new();
+        sim.app.world_mut().resource_mut::<Config>().sessions.idle_timeout = 1.0;
+
+        sim.send(event(SESSION, EventType::UserPromptSubmit));
+        sim.tick(30);
+        sim.send(event("session-b", EventType::UserPromptSubmit));
+        sim.tick(300);
+        assert_eq!(sim.rooms(), vec![None]);
+
+        sim.send(event("session-c", EventType::UserPromptSubmit));
+        sim.send(tool("session-c", EventType::PreToolUse, "Read", "toolu_read"));
+        sim.tick(2);
+        sim.app.world_mut().resource_mut::<Timeline>().paused = true;
+        sim.press(KeyCode::ArrowLeft);
+        assert_eq!(sim.app.world().resource::<Timeline>().position(), 3);
+        assert_eq!(sim.rooms(), vec![Some("session-c".to_string())]);
+        assert_eq!(sim.app.world().resource::<Office>().0.sessions.len(), 1);
+
+        // Before they closed, the rooms come back
+        sim.app.world_mut().resource_mut::<Timeline>().seek_to = Some(1);
+        sim.tick(1);
+        assert_eq!(sim.rooms(), vec![Some(SESSION.to_string())]);
+    }
+
+    #[test]
+    fn rooms_do_not_go_idle_while_the_timeline_is_paused() {
+        let mut sim = HeadlessApp::new();
+        sim.app.world_mut().resource_mut::<Config>().sessions.idle_timeout = 1.0;
+
+        sim.send(event(SESSION, EventType::UserPromptSubmit));
+        sim.tick(2);
+        sim.app.world_mut().resource_mut::<Timeline>().paused = true;
+        // Well past the timeout and the fade
+        sim.tick(400);
+        assert_eq!(sim.rooms(), vec![Some(SESSION.to_string())]);
+        assert!(!sim.app.world().resource::<Rooms>().rooms[0].fading);
+
+        // Idle time picks up where it left off
+        sim.app.world_mut().resource_mut::<Timeline>().paused = false;
+        sim.tick(30);
+        assert!(!sim.app.world().resource::<Rooms>().rooms[0].fading);
+        sim.tick(50);
+        assert!(sim.app.world().resource::<Rooms>().rooms[0].fading);
+    }
+
+    #[test]
+    fn session_end_sends_everyone_home() {
+        let mut sim = HeadlessApp::new();
+
+        sim.send(task(SESSION, EventType::PreToolUse, "toolu_task", "Explore"));
+        sim.send(event(SESSION, EventType::SessionEnd));
+        sim.tick(3);
+        assert_eq!(sim.agent_count(), 1);
+        assert!(!sim.app.world().resource::<Rooms>().rooms[0].open);
+    }
+}
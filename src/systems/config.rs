@@ -0,0 +1,263 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+
+use crate::agent_types::AgentTypes;
+use crate::components::Speed;
+use crate::config::{Config, WindowConfig};
+use crate::resources::{Office, StationOccupancy, Timeline};
+use crate::systems::agent::MainAgent;
+
+/// Watches the config file and reports when it may have changed
+#[derive(Resource)]
+pub struct ConfigWatcher {
+    path: PathBuf,
+    changed: Mutex<Receiver<()>>,
+    // Dropping the watcher stops it
+    _watcher: RecommendedWatcher,
+}
+
+impl ConfigWatcher {
+    /// Watch the directory holding the config, so editors that save by renaming are noticed too
+    fn spawn(path: &Path) -> notify::Result<Self> {
+        let dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        let file_name = path.file_name().map(|name| name.to_os_string());
+        let (sender, receiver) = mpsc::channel();
+
+        std::fs::create_dir_all(&dir)?;
+
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            let Ok(event) = res else {
+                return;
+            };
+            if !matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_)) {
+                return;
+            }
+
+            let touches_config = event
+                .paths
+                .iter()
+                .any(|p| p.file_name().map(|name| name.to_os_string()) == file_name);
+            if touches_config {
+                let _ = sender.send(());
+            }
+        })?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+
+        Ok(ConfigWatcher {
+            path: path.to_path_buf(),
+            changed: Mutex::new(receiver),
+            _watcher: watcher,
+        })
+    }
+}
+
+/// System to reload the config after the file changed, keeping the old one if it is invalid
+pub fn reload_config_system(watcher: Option<ResMut<ConfigWatcher>>, mut config: ResMut<Config>) {
+    let Some(mut watcher) = watcher else {
+        return;
+    };
+    let Ok(changed) = watcher.changed.get_mut() else {
+        return;
+    };
+
+    // One save can produce several notifications
+    if changed.try_iter().count() == 0 {
+        return;
+    }
+
+    match Config::load(&watcher.path).map(checked) {
+        Ok(reloaded) if reloaded != *config => {
+            if reloaded.events.path != config.events.path {
+                info!("The new events path takes effect after a restart");
+            }
+            info!("Reloaded {}", watcher.path.display());
+            *config = reloaded;
+        }
+        Ok(_) => {}
+        Err(e) => warn!("{:#}; keeping the previous settings", e),
+    }
+}
+
+/// The config with settings that cannot work replaced, warning about each
+/// once rather than on every spawn
+fn checked(mut config: Config) -> Config {
+    for problem in config.fix_invalid() {
+        warn!("Config: {}", problem);
+    }
+    config
+}
+
+/// System to push config changes into the window and the resources that use them
+pub fn apply_config_system(
+    mut commands: Commands,
+    config: Res<Config>,
+    mut applied_window: Local<Option<WindowConfig>>,
+    mut timeline: ResMut<Timeline>,
+    mut office: ResMut<Office>,
+    mut station_occupancy: ResMut<StationOccupancy>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !config.is_changed() {
+        return;
+    }
+
+    commands.insert_resource(ClearColor(config.window.clear_color()));
+    // The window opens at the configured size; after that a reload only resizes
+    // it when the size in the file changed, keeping any resizing by hand
+    let window_changed = applied_window
+        .as_ref()
+        .is_some_and(|applied| (applied.width, applied.height) != (config.window.width, config.window.height));
+    if window_changed && let Ok(mut window) = windows.get_single_mut() {
+        window.resolution.set(config.window.width, config.window.height);
+    }
+    *applied_window = Some(config.window.clone());
+
+    timeline.events_per_frame = config.events.per_frame;
+    timeline.history_limit = config.events.history_limit;
+    // Only touched when different, since a change re-targets every orbiting agent
+    if station_occupancy.orbit_radius != config.agents.orbit_radius {
+        station_occupancy.orbit_radius = config.agents.orbit_radius;
+    }
+
+    // Agents already working keep their look; new ones use the new types
+    let agent_types = AgentTypes::new(&config.agents.types);
+    if *office.0.agent_types != agent_types {
+        let agent_types = Arc::new(agent_types);
+        timeline.baseline.set_agent_types(agent_types.clone());
+        office.0.set_agent_types(agent_types);
+    }
+}
+
+/// System to give agents the configured speed, when spawned and when the config changes
+pub fn apply_agent_speed_system(config: Res<Config>, mut agents: Query<(&mut Speed, Has<MainAgent>)>) {
+    for (mut speed, is_main) in agents.iter_mut() {
+        if !config.is_changed() && !speed.is_added() {
+            continue;
+        }
+        speed.0 = if is_main {
+            config.agents.main_speed
+        } else {
+            config.agents.subagent_speed
+        };
+    }
+}
+
+/// System plugin for settings, optionally reloading them when the file changes.
+///
+/// Insert a loaded `Config` before adding it; otherwise the defaults are used.
+#[derive(Default)]
+pub struct ConfigPlugin {
+    /// Config file to watch
+    pub watch: Option<PathBuf>,
+}
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Config>();
+        let mut config = app.world_mut().resource_mut::<Config>();
+        *config = checked(std::mem::take(&mut *config));
+
+        app.init_resource::<Timeline>()
+            .init_resource::<Office>()
+            .init_resource::<StationOccupancy>()
+            .add_systems(Update, (
+                reload_config_system,
+                apply_config_system
+                    .after(reload_config_system)
+                    .before(crate::systems::agent::process_events_system),
+                apply_agent_speed_system
+                    .after(reload_config_system)
+                    .after(crate::systems::agent::process_events_system),
+            ));
+
+        if let Some(path) = &self.watch {
+            match ConfigWatcher::spawn(path) {
+                Ok(watcher) => {
+                    app.insert_resource(watcher);
+                }
+                Err(e) => warn!("Not watching {} for changes: {}", path.display(), e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Agent;
+    use crate::events::EventType;
+    use crate::harness::{task, tool, HeadlessApp};
+
+    const SESSION: &str = "session-a";
+
+    fn speeds(sim: &mut HeadlessApp) -> Vec<(bool, f32)> {
+        let world = sim.app.world_mut();
+        let mut speeds: Vec<_> = world
+            .query_filtered::<(&Speed, Has<MainAgent>), With<Agent>>()
+            .iter(world)
+            .map(|(speed, is_main)| (is_main, speed.0))
+            .collect();
+        // Main agent first
+        speeds.sort_by_key(|(is_main, _)| !is_main);
+        speeds
+    }
+
+    #[test]
+    fn changed_settings_apply_to_agents_already_working() {
+        let mut sim = HeadlessApp::new();
+        sim.send(task(SESSION, EventType::PreToolUse, "toolu_task", "Explore"));
+        sim.tick(1);
+        assert_eq!(speeds(&mut sim), vec![(true, 150.0), (false, 180.0)]);
+
+        {
+            let mut config = sim.app.world_mut().resource_mut::<Config>();
+            config.agents.main_speed = 300.0;
+            config.agents.subagent_speed = 90.0;
+            config.agents.orbit_radius = 60.0;
+            config.events.per_frame = 1;
+        }
+        sim.tick(1);
+        assert_eq!(speeds(&mut sim), vec![(true, 300.0), (false, 90.0)]);
+        assert_eq!(sim.app.world().resource::<StationOccupancy>().orbit_radius, 60.0);
+
+        // Only one event a frame gets through now
+        sim.send(tool(SESSION, EventType::PreToolUse, "Read", "toolu_1"));
+        sim.send(tool(SESSION, EventType::PostToolUse, "Read", "toolu_1"));
+        sim.tick(1);
+        assert_eq!(sim.app.world().resource::<Timeline>().position(), 2);
+        sim.tick(1);
+        assert_eq!(sim.app.world().resource::<Timeline>().position(), 3);
+    }
+
+    #[test]
+    fn new_agents_get_the_configured_speed() {
+        let mut sim = HeadlessApp::new();
+        sim.app.world_mut().resource_mut::<Config>().agents.subagent_speed = 75.0;
+        sim.tick(1);
+
+        sim.send(task(SESSION, EventType::PreToolUse, "toolu_task", "Plan"));
+        sim.tick(1);
+        assert_eq!(speeds(&mut sim)[1], (false, 75.0));
+    }
+
+    #[test]
+    fn unknown_characters_and_colours_out_of_range_are_fixed_on_load() {
+        let config: Config = toml::from_str(
+            "[[agents.types]]\npattern = \"db-*\"\nsprite = \"Nobody\"\ncolor = [1.5, -0.2, 0.5]\n\n\
+             [[agents.types]]\npattern = \"*-reviewer\"\nsprite = \"levi\"\n",
+        )
+        .unwrap();
+        let config = checked(config);
+
+        let [migrator, reviewer] = &config.agents.types[..] else {
+            panic!("expected two agent types");
+        };
+        assert_eq!(migrator.sprite, None);
+        assert_eq!(migrator.color, Some([1.0, 0.0, 0.5]));
+        assert_eq!(reviewer.sprite.as_deref(), Some("levi"));
+    }
+}
@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+use chrono::Utc;
+
+use crate::components::DemoStatusText;
+use crate::demo::DemoGenerator;
+use crate::events::ToolEvent;
+use crate::resources::{Diagnostics, EventQueue, GameState};
+use crate::systems::event_reader::ingest_event;
+
+/// A generated session being played as if it were live
+#[derive(Resource)]
+pub struct DemoState {
+    pub seed: u64,
+    pub speed: f32,
+    /// Seconds of the generated session played so far
+    pub elapsed: f64,
+    generator: DemoGenerator,
+    /// The next event and when it is due
+    upcoming: (f64, ToolEvent),
+}
+
+impl DemoState {
+    pub fn new(seed: u64, speed: f32) -> Self {
+        let mut generator = DemoGenerator::new(seed, Utc::now());
+        let upcoming = generator.next_event();
+        DemoState {
+            seed,
+            speed: speed.max(0.1),
+            elapsed: 0.0,
+            generator,
+            upcoming,
+        }
+    }
+}
+
+/// System that feeds generated events in as their time comes
+pub fn demo_events_system(
+    time: Res<Time>,
+    mut demo: ResMut<DemoState>,
+    mut event_queue: ResMut<EventQueue>,
+    mut game_state: ResMut<GameState>,
+    mut diagnostics: ResMut<Diagnostics>,
+) {
+    demo.elapsed += time.delta_secs_f64() * demo.speed as f64;
+
+    while demo.upcoming.0 <= demo.elapsed {
+        let next = demo.generator.next_event();
+        let (_, event) = std::mem::replace(&mut demo.upcoming, next);
+        ingest_event(event, &mut game_state, &mut event_queue, &mut diagnostics);
+    }
+}
+
+/// System to set up the demo indicator
+pub fn setup_demo_ui(mut commands: Commands, demo: Res<DemoState>) {
+    commands.spawn((
+        Text::new(format!("Demo (seed {})", demo.seed)),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.8, 0.3)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(38.0),
+            right: Val::Px(10.0),
+            ..default()
+        },
+        DemoStatusText,
+    ));
+}
+
+/// System plugin for playing a generated session instead of watching live
+pub struct DemoPlugin {
+    pub seed: u64,
+    pub speed: f32,
+}
+
+impl Plugin for DemoPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DemoState::new(self.seed, self.speed))
+            .init_resource::<EventQueue>()
+            .init_resource::<GameState>()
+            .init_resource::<Diagnostics>()
+            .add_systems(Startup, setup_demo_ui)
+            .add_systems(Update, demo_events_system);
+    }
+}
@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::resources::{Diagnostics, EventQueue};
+
+/// System to set up the diagnostics overlay and its hint, both hidden at first
+pub fn setup_diagnostics_ui(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(38.0),
+                left: Val::Px(10.0),
+                max_width: Val::Px(560.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.08, 0.9)),
+            Visibility::Hidden,
+            DiagnosticsPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.85, 0.85, 0.85)),
+                DiagnosticsText,
+            ));
+        });
+
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.4, 0.3)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(38.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        Visibility::Hidden,
+        DiagnosticsHint,
+    ));
+}
+
+/// System to show or hide the overlay with F3
+pub fn toggle_diagnostics_system(keys: Res<ButtonInput<KeyCode>>, mut diagnostics: ResMut<Diagnostics>) {
+    if keys.just_pressed(KeyCode::F3) {
+        diagnostics.visible = !diagnostics.visible;
+    }
+}
+
+/// System to keep the overlay and hint in sync with the counters
+pub fn update_diagnostics_ui_system(
+    diagnostics: Res<Diagnostics>,
+    event_queue: Res<EventQueue>,
+    mut panel: Query<&mut Visibility, (With<DiagnosticsPanel>, Without<DiagnosticsHint>)>,
+    mut hint: Query<(&mut Text, &mut Visibility), With<DiagnosticsHint>>,
+    mut text: Query<&mut Text, (With<DiagnosticsText>, Without<DiagnosticsHint>)>,
+) {
+    if !diagnostics.is_changed() && !event_queue.is_changed() {
+        return;
+    }
+
+    let failures = diagnostics.dropped + diagnostics.io_errors;
+
+    if let Ok(mut visibility) = panel.get_single_mut() {
+        *visibility = if diagnostics.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    if let Ok((mut text, mut visibility)) = hint.get_single_mut() {
+        **text = format!("{} ingestion problems (F3)", failures);
+        *visibility = if failures > 0 && !diagnostics.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    if let Ok(mut text) = text.get_single_mut() {
+        let mut report = format!(
+            "Lines read: {}\nDropped: {}  Duplicates: {} (window {})  Unknown: {}  I/O errors: {}",
+            diagnostics.lines_read,
+            diagnostics.dropped,
+            event_queue.dedup.suppressed,
+            event_queue.dedup.capacity(),
+            diagnostics.unknown,
+            diagnostics.io_errors
+        );
+
+        if diagnostics.recent.is_empty() {
+            report.push_str("\n\nNo failures");
+        } else {
+            report.push_str("\n\nRecent failures:");
+            for failure in diagnostics.recent.iter().rev() {
+                match failure.line_number {
+                    Some(line) => report.push_str(&format!(
+                        "\n{}:{}: {}",
+                        failure.source, line, failure.message
+                    )),
+                    None => report.push_str(&format!("\n{}: {}", failure.source, failure.message)),
+                }
+            }
+        }
+
+        **text = report;
+    }
+}
+
+/// System plugin for the ingestion diagnostics overlay
+pub struct DiagnosticsPlugin;
+
+impl Plugin for DiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Diagnostics>()
+            .init_resource::<EventQueue>()
+            .add_systems(Startup, setup_diagnostics_ui)
+            .add_systems(Update, (
+                toggle_diagnostics_system,
+                update_diagnostics_ui_system.after(toggle_diagnostics_system),
+            ));
+    }
+}
@@ -0,0 +1,237 @@
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::resources::Rooms;
+use crate::systems::agent::MainAgent;
+
+/// How long a new task bubble stays up
+const TASK_BUBBLE_SECS: f32 = 4.0;
+
+/// How long the compaction effect lasts
+const COMPACTION_SECS: f32 = 1.5;
+
+/// Floor colour while a session is open
+const FLOOR_OPEN: Color = Color::srgba(0.15, 0.15, 0.2, 0.8);
+
+/// Floor colour once the session has ended
+const FLOOR_CLOSED: Color = Color::srgba(0.05, 0.05, 0.07, 0.8);
+
+/// Something for the main agent of one room to show, sent by `process_events_system`
+#[derive(Event, Debug, Clone)]
+pub struct HookSignal {
+    /// Main agent of the room showing the session
+    pub agent: Entity,
+    pub kind: Signal,
+}
+
+/// Session lifecycle moments that get their own visual
+#[derive(Debug, Clone)]
+pub enum Signal {
+    /// The user submitted a prompt; carries a short summary of it
+    PromptSubmitted(String),
+    /// Claude finished its turn and is waiting for the user
+    TurnEnded,
+    /// Claude needs the user, usually to approve a tool; carries the message
+    AttentionNeeded(String),
+    SessionStarted,
+    SessionEnded,
+    Compacting,
+    /// A tool call started or finished, so the main agent is busy again
+    ToolActivity,
+}
+
+/// System to turn hook signals into visuals on the main agent of each room
+pub fn apply_hook_signals_system(
+    mut commands: Commands,
+    mut signals: EventReader<HookSignal>,
+    main_agents: Query<(), With<MainAgent>>,
+    bubbles: Query<(Entity, &SpeechBubble, &Parent)>,
+) {
+    for HookSignal { agent: main_entity, kind } in signals.read() {
+        let main_entity = *main_entity;
+        // The room may have faded away since
+        if !main_agents.contains(main_entity) {
+            continue;
+        }
+
+        match kind {
+            Signal::PromptSubmitted(prompt) => {
+                commands.entity(main_entity).remove::<Resting>();
+                clear_bubbles(&mut commands, &bubbles, main_entity, None);
+                spawn_bubble(
+                    &mut commands,
+                    main_entity,
+                    BubbleKind::Task,
+                    prompt,
+                    Some(TASK_BUBBLE_SECS),
+                );
+            }
+            Signal::TurnEnded => {
+                clear_bubbles(&mut commands, &bubbles, main_entity, Some(BubbleKind::Alert));
+                commands.entity(main_entity).insert(Resting);
+            }
+            Signal::AttentionNeeded(message) => {
+                clear_bubbles(&mut commands, &bubbles, main_entity, Some(BubbleKind::Alert));
+                let text = if message.is_empty() { "!" } else { message.as_str() };
+                spawn_bubble(&mut commands, main_entity, BubbleKind::Alert, text, None);
+            }
+            Signal::SessionStarted => {
+                commands.entity(main_entity).remove::<Resting>();
+            }
+            Signal::SessionEnded => {
+                clear_bubbles(&mut commands, &bubbles, main_entity, None);
+                commands.entity(main_entity).insert(Resting);
+            }
+            Signal::Compacting => {
+                commands.entity(main_entity).insert(Compacting {
+                    timer: Timer::from_seconds(COMPACTION_SECS, TimerMode::Once),
+                });
+                spawn_bubble(
+                    &mut commands,
+                    main_entity,
+                    BubbleKind::Compaction,
+                    "Compacting...",
+                    Some(COMPACTION_SECS),
+                );
+            }
+            Signal::ToolActivity => {
+                commands.entity(main_entity).remove::<Resting>();
+                clear_bubbles(&mut commands, &bubbles, main_entity, Some(BubbleKind::Alert));
+            }
+        }
+    }
+}
+
+/// Despawn the agent's bubbles of the given kind, or all of them
+fn clear_bubbles(
+    commands: &mut Commands,
+    bubbles: &Query<(Entity, &SpeechBubble, &Parent)>,
+    agent: Entity,
+    kind: Option<BubbleKind>,
+) {
+    for (entity, bubble, parent) in bubbles.iter() {
+        if parent.get() == agent && kind.is_none_or(|kind| kind == bubble.kind) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Attach a speech bubble above the main agent
+fn spawn_bubble(
+    commands: &mut Commands,
+    agent: Entity,
+    kind: BubbleKind,
+    text: &str,
+    seconds: Option<f32>,
+) {
+    let (background, text_color) = match kind {
+        BubbleKind::Task => (Color::srgba(0.95, 0.95, 0.95, 0.9), Color::srgb(0.1, 0.1, 0.1)),
+        BubbleKind::Alert => (Color::srgba(0.9, 0.2, 0.2, 0.9), Color::WHITE),
+        BubbleKind::Compaction => (Color::srgba(0.5, 0.3, 0.8, 0.9), Color::WHITE),
+    };
+    let width = (text.chars().count() as f32 * 6.0 + 12.0).clamp(24.0, 320.0);
+
+    commands.entity(agent).with_children(|parent| {
+        parent
+            .spawn((
+                Sprite {
+                    color: background,
+                    custom_size: Some(Vec2::new(width, 18.0)),
+                    ..default()
+                },
+                Transform::from_xyz(0.0, 56.0, 2.0),
+                SpeechBubble {
+                    kind,
+                    timer: seconds.map(|secs| Timer::from_seconds(secs, TimerMode::Once)),
+                },
+            ))
+            .with_children(|bubble| {
+                bubble.spawn((
+                    Text2d::new(text),
+                    TextFont {
+                        font_size: 11.0,
+                        ..default()
+                    },
+                    TextColor(text_color),
+                    Transform::from_xyz(0.0, 0.0, 0.5),
+                ));
+            });
+    });
+}
+
+/// System to expire timed bubbles and the compaction effect
+pub fn expire_effects_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bubbles: Query<(Entity, &mut SpeechBubble)>,
+    mut compacting: Query<(Entity, &mut Compacting)>,
+) {
+    for (entity, mut bubble) in bubbles.iter_mut() {
+        let expired = bubble
+            .timer
+            .as_mut()
+            .is_some_and(|timer| timer.tick(time.delta()).finished());
+        if expired {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    for (entity, mut effect) in compacting.iter_mut() {
+        if effect.timer.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Compacting>();
+        }
+    }
+}
+
+/// System to sit the main agent down while it waits, on top of `animation_system`
+pub fn resting_pose_system(mut resting: Query<(&mut Transform, &AnimationController), With<Resting>>) {
+    for (mut transform, anim) in resting.iter_mut() {
+        // A little wider and shorter, no breathing
+        if anim.state == AnimationState::Idle {
+            transform.scale = Vec3::new(1.05, 0.85, 1.0);
+        }
+    }
+}
+
+/// System to squeeze the main agent while the context is compacted
+pub fn compaction_pose_system(mut compacting: Query<(&mut Transform, &Compacting)>) {
+    for (mut transform, effect) in compacting.iter_mut() {
+        // Squeeze down and spring back over the length of the effect
+        let squeeze = (effect.timer.fraction() * std::f32::consts::PI).sin() * 0.4;
+        transform.scale = Vec3::new(1.0 + squeeze * 0.5, 1.0 - squeeze, 1.0);
+    }
+}
+
+/// System to fade the lights of each room in and out with its session
+pub fn office_lighting_system(
+    time: Res<Time>,
+    rooms: Res<Rooms>,
+    mut floors: Query<&mut Sprite, (With<OfficeFloor>, Without<FadeBase>)>,
+) {
+    for room in &rooms.rooms {
+        let Ok(mut sprite) = floors.get_mut(room.floor) else {
+            continue;
+        };
+        let target = if room.open { FLOOR_OPEN } else { FLOOR_CLOSED };
+
+        let current = sprite.color.to_srgba();
+        let blend = (time.delta_secs() * 3.0).min(1.0);
+        sprite.color = Color::Srgba(current.mix(&target.to_srgba(), blend));
+    }
+}
+
+/// System plugin for hook event visuals
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<HookSignal>()
+            .add_systems(Update, (
+                apply_hook_signals_system.after(crate::systems::agent::process_events_system),
+                expire_effects_system,
+                resting_pose_system.after(crate::systems::animation::animation_system),
+                compaction_pose_system.after(resting_pose_system),
+                office_lighting_system,
+            ));
+    }
+}
@@ -1,73 +1,342 @@
 use bevy::prelude::*;
-use std::fs::File;
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
 
-use crate::events::parse_event;
-use crate::resources::{EventQueue, FileWatcherState, GameState};
+use crate::events::{EventParser, EventType, ToolEvent};
+use crate::resources::{Diagnostics, EventQueue, GameState};
+use crate::sources::{default_events_path, EventSource, SourceEvent, SourceSender, SourceSpec};
+use crate::systems::workspace::SessionClosed;
+use crate::transcript::{self, Backfill, Callers};
 
-/// System that reads new events from the JSONL file
+/// What the ingestion thread hands over to the app
+pub enum Ingested {
+    Event(Box<ToolEvent>),
+    /// History of a session that was already running before its first event,
+    /// sent just ahead of that event
+    Backfill { session_id: String, backfill: Backfill },
+    /// An events file was truncated or replaced
+    Rotated,
+    /// A line that could not be parsed
+    Dropped {
+        source: String,
+        line_number: u64,
+        message: String,
+    },
+    /// A source or transcript could not be read
+    IoError { source: String, message: String },
+}
+
+/// Receiving end of the ingestion thread
+#[derive(Resource)]
+pub struct IngestReceiver {
+    receiver: Mutex<Receiver<Ingested>>,
+    /// Sessions the ingestion thread can stop keeping transcript state for
+    closed: Mutex<Sender<String>>,
+}
+
+/// Event sources to open at startup
+#[derive(Resource)]
+pub struct EventSources(Mutex<Vec<Box<dyn EventSource>>>);
+
+/// Open every configured event source, skipping those that fail, and start
+/// reading them on a background thread
+pub fn open_event_sources(mut commands: Commands, mut pending: ResMut<EventSources>) {
+    let mut sources = Vec::new();
+    let (source_sender, source_receiver) = mpsc::channel();
+    let stopped = Arc::new(AtomicBool::new(false));
+
+    let pending = pending.0.get_mut().map(std::mem::take).unwrap_or_default();
+    for mut source in pending {
+        let sender = SourceSender::new(sources.len(), source_sender.clone(), stopped.clone());
+        match source.open(sender) {
+            Ok(()) => {
+                info!("Reading events from {}", source.describe());
+                sources.push(source);
+            }
+            Err(err) => error!("Could not open event source {}: {}", source.describe(), err),
+        }
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let (closed_sender, closed_receiver) = mpsc::channel();
+    let spawned = std::thread::Builder::new()
+        .name("event-ingest".to_string())
+        .spawn(move || {
+            run_ingestion(sources, source_receiver, sender, closed_receiver);
+            // Let the sources' threads wind down
+            stopped.store(true, Ordering::Relaxed);
+        });
+    if let Err(err) = spawned {
+        error!("Could not start the event reader thread: {}", err);
+    }
+
+    commands.insert_resource(IngestReceiver {
+        receiver: Mutex::new(receiver),
+        closed: Mutex::new(closed_sender),
+    });
+}
+
+/// Wait for what the sources read, parse it and send it to the app.
+///
+/// Runs until the app drops the receiving end or every source has finished.
+/// File reads and JSON parsing of large tool responses happen here so they
+/// never hold up a frame.
+fn run_ingestion(
+    sources: Vec<Box<dyn EventSource>>,
+    source_events: Receiver<(usize, SourceEvent)>,
+    sender: Sender<Ingested>,
+    closed: Receiver<String>,
+) {
+    // Sessions whose transcript has already been read for backfill
+    let mut backfilled = HashSet::new();
+    // Who made which tool call, per session
+    let mut callers = HashMap::new();
+    let mut parsers: Vec<EventParser> = sources.iter().map(|_| EventParser::live()).collect();
+
+    while let Ok((index, source_event)) = source_events.recv() {
+        for session_id in closed.try_iter() {
+            callers.remove(&session_id);
+        }
+
+        let source = sources[index].as_ref();
+        let parser = &mut parsers[index];
+        for ingested in ingest_source_event(source, parser, source_event, &mut backfilled, &mut callers) {
+            if sender.send(ingested).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Turn one thing a source produced into what the app should hear about
+fn ingest_source_event(
+    source: &dyn EventSource,
+    parser: &mut EventParser,
+    source_event: SourceEvent,
+    backfilled: &mut HashSet<String>,
+    callers: &mut HashMap<String, Callers>,
+) -> Vec<Ingested> {
+    let (line, line_number) = match source_event {
+        SourceEvent::Line { text, number } => (text, number),
+        SourceEvent::Rotated => return vec![Ingested::Rotated],
+        SourceEvent::Error(message) => {
+            return vec![Ingested::IoError {
+                source: source.describe(),
+                message,
+            }];
+        }
+    };
+
+    let mut event = match parser.parse(&line, line_number) {
+        Ok(event) => event,
+        Err(err) => {
+            return vec![Ingested::Dropped {
+                source: source.describe(),
+                line_number,
+                message: err.to_string(),
+            }];
+        }
+    };
+
+    attribute(&mut event, callers);
+    if event.event_type == EventType::SessionEnd {
+        callers.remove(&event.session_id);
+    }
+
+    let mut ingested = Vec::new();
+    if backfilled.insert(event.session_id.clone()) {
+        ingested.extend(read_backfill(&event));
+    }
+    ingested.push(Ingested::Event(Box::new(event)));
+    ingested
+}
+
+/// Look the tool call up in the session's transcript to tell which agent made it.
+///
+/// Calls not found there are left to the in-flight Task context; see `State::apply`.
+fn attribute(event: &mut ToolEvent, callers: &mut HashMap<String, Callers>) {
+    if event.event_type != EventType::PreToolUse || event.transcript_path.is_empty() {
+        return;
+    }
+
+    let index = callers.entry(event.session_id.clone()).or_default();
+    if index.update(Path::new(&event.transcript_path)).is_err() {
+        return;
+    }
+    event.parent_tool_use_id = index
+        .caller(&event.tool_use_id)
+        .map(|task| task.unwrap_or_default().to_string());
+}
+
+/// Read the transcript of a session that was already running before its first event arrived
+fn read_backfill(event: &ToolEvent) -> Option<Ingested> {
+    // A fresh session has no history worth reading
+    if event.event_type == EventType::SessionStart || event.transcript_path.is_empty() {
+        return None;
+    }
+
+    match transcript::backfill(&event.session_id, Path::new(&event.transcript_path)) {
+        Ok(backfill) => Some(Ingested::Backfill {
+            session_id: event.session_id.clone(),
+            backfill,
+        }),
+        Err(err) => Some(Ingested::IoError {
+            source: event.transcript_path.clone(),
+            message: err.to_string(),
+        }),
+    }
+}
+
+/// System that queues whatever the ingestion thread has parsed since last frame
 pub fn read_events_system(
-    mut file_state: ResMut<FileWatcherState>,
+    time: Res<Time>,
+    mut ingest: ResMut<IngestReceiver>,
     mut event_queue: ResMut<EventQueue>,
     mut game_state: ResMut<GameState>,
+    mut diagnostics: ResMut<Diagnostics>,
 ) {
-    // Try to open and read the events file
-    let Ok(file) = File::open(&file_state.events_path) else {
+    let Ok(receiver) = ingest.receiver.get_mut() else {
         return;
     };
 
-    let mut reader = BufReader::new(file);
+    for ingested in receiver.try_iter() {
+        match ingested {
+            Ingested::Event(event) => {
+                diagnostics.lines_read += 1;
+                ingest_event(*event, &mut game_state, &mut event_queue, &mut diagnostics);
+            }
+            Ingested::Backfill { session_id, backfill } => {
+                apply_backfill(&session_id, backfill, &mut game_state, &mut event_queue, &mut diagnostics);
+            }
+            Ingested::Rotated => game_state.log_rotated_at = Some(time.elapsed_secs()),
+            Ingested::Dropped {
+                source,
+                line_number,
+                message,
+            } => {
+                warn!("Skipping line {} of {}: {}", line_number, source, message);
+                diagnostics.lines_read += 1;
+                diagnostics.record_dropped(&source, line_number, message);
+            }
+            Ingested::IoError { source, message } => {
+                warn!("Error reading {}: {}", source, message);
+                diagnostics.record_io_error(&source, message);
+            }
+        }
+    }
+}
 
-    // Seek to last known position
-    if reader.seek(SeekFrom::Start(file_state.last_position)).is_err() {
+/// System to let the ingestion thread forget sessions whose room has closed
+pub fn forget_closed_sessions_system(mut ingest: ResMut<IngestReceiver>, mut closed: EventReader<SessionClosed>) {
+    let Ok(sender) = ingest.closed.get_mut() else {
         return;
+    };
+    for event in closed.read() {
+        let _ = sender.send(event.session_id.clone());
     }
+}
 
-    // Read new lines
-    let mut line = String::new();
-    while let Ok(bytes_read) = reader.read_line(&mut line) {
-        if bytes_read == 0 {
-            break;
-        }
+/// Catch up on a session that was already running before its first event arrived
+fn apply_backfill(
+    session_id: &str,
+    backfill: Backfill,
+    game_state: &mut GameState,
+    event_queue: &mut EventQueue,
+    diagnostics: &mut Diagnostics,
+) {
+    info!(
+        "Backfilled session {} from its transcript: {} tool calls, {} still relevant",
+        session_id,
+        backfill.tool_calls,
+        backfill.events.len()
+    );
 
-        let trimmed = line.trim();
-        if !trimmed.is_empty() {
-            if let Some(event) = parse_event(trimmed) {
-                // Detect session change - mark for agent cleanup
-                let session_changed = game_state.session_id.as_ref()
-                    != Some(&event.session_id);
-
-                if session_changed {
-                    game_state.session_changed = true;
-                }
-
-                // Update game state
-                game_state.session_id = Some(event.session_id.clone());
-                game_state.current_tool = Some(event.tool_name.clone());
-                game_state.current_tool_input = Some(event.input_summary());
-                game_state.events_processed += 1;
-
-                // Queue the event for processing
-                event_queue.push(event);
-            }
-        }
-        line.clear();
+    let at = backfill.events.first().map_or_else(Utc::now, |event| event.timestamp);
+    game_state.follow(session_id);
+
+    // The replayed events count themselves as they are ingested
+    game_state.session_entry(session_id, at).tool_calls +=
+        backfill.tool_calls.saturating_sub(backfill.events.len());
+    for past_event in backfill.events {
+        ingest_event(past_event, game_state, event_queue, diagnostics);
+    }
+}
+
+/// Record a freshly read event in the game state and queue it for processing
+pub fn ingest_event(
+    event: ToolEvent,
+    game_state: &mut GameState,
+    event_queue: &mut EventQueue,
+    diagnostics: &mut Diagnostics,
+) {
+    game_state.follow(&event.session_id);
+
+    // Update game state
+    let info = game_state.session_entry(&event.session_id, event.timestamp);
+    info.last_activity = info.last_activity.max(event.timestamp);
+    // A session that went quiet gets its room back
+    info.closed = false;
+    info.activity = Some((event.activity_name(), event.input_summary()));
+    if !event.cwd.is_empty() {
+        info.cwd = Some(event.cwd.clone());
+    }
+    if event.event_type == EventType::PreToolUse {
+        info.tool_calls += 1;
     }
+    game_state.events_processed += 1;
 
-    // Update position
-    if let Ok(pos) = reader.stream_position() {
-        file_state.last_position = pos;
+    if event.event_type == EventType::Unknown {
+        diagnostics.unknown += 1;
     }
+
+    // Queue the event for processing
+    event_queue.push(event);
 }
 
 /// System plugin for event reading
-pub struct EventReaderPlugin;
+pub struct EventReaderPlugin {
+    /// Opened at startup, then handed to the ingestion thread
+    sources: Mutex<Vec<Box<dyn EventSource>>>,
+}
+
+impl EventReaderPlugin {
+    /// Read from `sources`, which may include ones defined outside this crate
+    pub fn new(sources: Vec<Box<dyn EventSource>>) -> Self {
+        EventReaderPlugin {
+            sources: Mutex::new(sources),
+        }
+    }
+
+    /// Read from the built-in sources given on the command line
+    pub fn from_specs(specs: &[SourceSpec]) -> Self {
+        EventReaderPlugin::new(specs.iter().map(SourceSpec::source).collect())
+    }
+}
+
+impl Default for EventReaderPlugin {
+    /// Tail the default events file, without looking at the config file
+    fn default() -> Self {
+        EventReaderPlugin::from_specs(&[SourceSpec::File(default_events_path(None))])
+    }
+}
 
 impl Plugin for EventReaderPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<FileWatcherState>()
+        let sources = self
+            .sources
+            .lock()
+            .map(|mut sources| std::mem::take(&mut *sources))
+            .unwrap_or_default();
+        app.insert_resource(EventSources(Mutex::new(sources)))
             .init_resource::<EventQueue>()
             .init_resource::<GameState>()
-            .add_systems(Update, read_events_system);
+            .init_resource::<Diagnostics>()
+            .add_systems(Startup, open_event_sources)
+            .add_event::<SessionClosed>()
+            .add_systems(Update, (read_events_system, forget_closed_sessions_system));
     }
 }
@@ -0,0 +1,48 @@
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::systems::agent::MainAgent;
+
+/// How far from each end a tether stops, so it does not cover the sprites
+const TETHER_GAP: f32 = 20.0;
+
+/// System to draw a tether from each subagent to the agent that started it.
+///
+/// Subagents started by another subagent get a brighter tether, so delegation
+/// stands out against the main agent's own Tasks.
+pub fn draw_tethers_system(
+    mut gizmos: Gizmos,
+    subagents: Query<(&GlobalTransform, &SpawnedBy, &Agent, &Sprite)>,
+    parents: Query<(&GlobalTransform, Has<MainAgent>)>,
+) {
+    for (transform, spawned_by, agent, sprite) in subagents.iter() {
+        // Orphans have lost theirs
+        let Ok((parent_transform, parent_is_main)) = parents.get(spawned_by.0) else {
+            continue;
+        };
+
+        let from = parent_transform.translation().truncate();
+        let to = transform.translation().truncate();
+        let length = from.distance(to);
+        if length <= TETHER_GAP * 2.0 {
+            continue;
+        }
+        let direction = (to - from) / length;
+
+        let strength = if parent_is_main { 0.25 } else { 0.7 };
+        let color = agent
+            .agent_type
+            .color()
+            .with_alpha(strength * sprite.color.alpha());
+        gizmos.line_2d(from + direction * TETHER_GAP, to - direction * TETHER_GAP, color);
+    }
+}
+
+/// System plugin for drawing which agent started which
+pub struct HierarchyPlugin;
+
+impl Plugin for HierarchyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, draw_tethers_system);
+    }
+}
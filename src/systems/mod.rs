@@ -3,9 +3,27 @@ pub mod agent;
 pub mod movement;
 pub mod animation;
 pub mod ui;
+pub mod replay;
+pub mod demo;
+pub mod timeline;
+pub mod effects;
+pub mod diagnostics;
+pub mod workspace;
+pub mod config;
+pub mod session_panel;
+pub mod hierarchy;
 
 pub use event_reader::*;
 pub use agent::*;
 pub use movement::*;
 pub use animation::*;
 pub use ui::*;
+pub use replay::*;
+pub use demo::*;
+pub use timeline::*;
+pub use effects::*;
+pub use diagnostics::*;
+pub use workspace::*;
+pub use config::*;
+pub use session_panel::*;
+pub use hierarchy::*;
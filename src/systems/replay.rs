@@ -0,0 +1,228 @@
+use bevy::prelude::*;
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+use crate::archive::{self, LoadProblem};
+use crate::components::ReplayStatusText;
+use crate::events::ToolEvent;
+use crate::resources::{Diagnostics, EventQueue, GameState};
+use crate::systems::event_reader::ingest_event;
+
+/// Playback speeds selectable with the `[` and `]` keys
+pub const SPEED_STEPS: [f32; 7] = [0.5, 1.0, 2.0, 5.0, 10.0, 15.0, 20.0];
+
+/// Longest pause between two events that is played back in full, in seconds.
+/// Recorded sessions can sit idle for hours, which is not worth watching.
+const MAX_IDLE_GAP_SECS: f64 = 10.0;
+
+/// An event waiting for its turn during replay
+struct ScheduledEvent {
+    /// Seconds after the start of the recording at which the event is due
+    due: f64,
+    event: ToolEvent,
+}
+
+/// State of a replay of a recorded events file or a directory of them
+#[derive(Resource)]
+pub struct ReplayState {
+    pub path: PathBuf,
+    pub speed: f32,
+    /// Seconds of recording played back so far
+    pub elapsed: f64,
+    pub total_events: usize,
+    pending: VecDeque<ScheduledEvent>,
+}
+
+impl ReplayState {
+    pub fn new(path: PathBuf, speed: f32) -> Self {
+        ReplayState {
+            path,
+            speed: clamp_speed(speed),
+            elapsed: 0.0,
+            total_events: 0,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Load every event in the file, or in each log of a directory, and
+    /// schedule it by its timestamp, counting lines that could not be parsed
+    pub fn load(&mut self, diagnostics: &mut Diagnostics) -> std::io::Result<()> {
+        let recording = archive::load_recording(&self.path)?;
+
+        diagnostics.lines_read += recording.lines_read;
+        for problem in recording.problems {
+            match problem {
+                LoadProblem::Dropped {
+                    source,
+                    line_number,
+                    message,
+                } => diagnostics.record_dropped(&source, line_number, message),
+                LoadProblem::IoError { source, message } => {
+                    warn!("Error reading {}: {}", source, message);
+                    diagnostics.record_io_error(&source, message);
+                }
+            }
+        }
+
+        self.pending = schedule(recording.events);
+        self.total_events = self.pending.len();
+        self.elapsed = 0.0;
+        Ok(())
+    }
+
+    pub fn events_played(&self) -> usize {
+        self.total_events - self.pending.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Step the playback speed up or down through `SPEED_STEPS`
+    pub fn change_speed(&mut self, faster: bool) {
+        let current = SPEED_STEPS
+            .iter()
+            .position(|&step| step >= self.speed)
+            .unwrap_or(SPEED_STEPS.len() - 1);
+        let next = if faster {
+            (current + 1).min(SPEED_STEPS.len() - 1)
+        } else {
+            current.saturating_sub(1)
+        };
+        self.speed = SPEED_STEPS[next];
+    }
+}
+
+fn clamp_speed(speed: f32) -> f32 {
+    speed.clamp(SPEED_STEPS[0], SPEED_STEPS[SPEED_STEPS.len() - 1])
+}
+
+/// Turn recorded timestamps into due times relative to the first event.
+///
+/// Events stay in file order; one stamped earlier than its predecessor is
+/// played right after it rather than reordered.
+fn schedule(events: Vec<ToolEvent>) -> VecDeque<ScheduledEvent> {
+    let mut scheduled = VecDeque::with_capacity(events.len());
+    let mut due = 0.0;
+    let mut previous: Option<DateTime<Utc>> = None;
+
+    for event in events {
+        if let Some(previous) = previous {
+            let gap = (event.timestamp - previous).num_milliseconds() as f64 / 1000.0;
+            due += gap.clamp(0.0, MAX_IDLE_GAP_SECS);
+        }
+        previous = Some(event.timestamp);
+        scheduled.push_back(ScheduledEvent { due, event });
+    }
+
+    scheduled
+}
+
+/// Load the recording when the app starts
+pub fn load_replay(mut replay: ResMut<ReplayState>, mut diagnostics: ResMut<Diagnostics>) {
+    match replay.load(&mut diagnostics) {
+        Ok(()) => info!(
+            "Replaying {} events from {}, skipped {} unreadable lines",
+            replay.total_events,
+            replay.path.display(),
+            diagnostics.dropped
+        ),
+        Err(err) => {
+            error!("Could not read {}: {}", replay.path.display(), err);
+            let source = replay.path.display().to_string();
+            diagnostics.record_io_error(&source, err.to_string());
+        }
+    }
+}
+
+/// System that releases recorded events once their time has come
+pub fn replay_events_system(
+    time: Res<Time>,
+    mut replay: ResMut<ReplayState>,
+    mut event_queue: ResMut<EventQueue>,
+    mut game_state: ResMut<GameState>,
+    mut diagnostics: ResMut<Diagnostics>,
+) {
+    if replay.is_finished() {
+        return;
+    }
+
+    replay.elapsed += time.delta_secs_f64() * replay.speed as f64;
+
+    while replay.pending.front().is_some_and(|next| next.due <= replay.elapsed) {
+        if let Some(scheduled) = replay.pending.pop_front() {
+            ingest_event(scheduled.event, &mut game_state, &mut event_queue, &mut diagnostics);
+        }
+    }
+}
+
+/// System to change playback speed from the keyboard
+pub fn replay_speed_input_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut replay: ResMut<ReplayState>,
+) {
+    if keys.just_pressed(KeyCode::BracketRight) {
+        replay.change_speed(true);
+    }
+    if keys.just_pressed(KeyCode::BracketLeft) {
+        replay.change_speed(false);
+    }
+}
+
+/// System to set up the replay indicator
+pub fn setup_replay_ui(mut commands: Commands) {
+    commands.spawn((
+        Text::new("Replay"),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.8, 0.3)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(38.0),
+            right: Val::Px(10.0),
+            ..default()
+        },
+        ReplayStatusText,
+    ));
+}
+
+/// System to keep the replay indicator current
+pub fn update_replay_ui_system(
+    replay: Res<ReplayState>,
+    mut status_text: Query<&mut Text, With<ReplayStatusText>>,
+) {
+    if let Ok(mut text) = status_text.get_single_mut() {
+        let state = if replay.is_finished() { "done" } else { "playing" };
+        **text = format!(
+            "Replay {}x [{}] {}/{}",
+            replay.speed,
+            state,
+            replay.events_played(),
+            replay.total_events
+        );
+    }
+}
+
+/// System plugin for replaying a recorded events file instead of watching live
+pub struct ReplayPlugin {
+    pub path: PathBuf,
+    pub speed: f32,
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ReplayState::new(self.path.clone(), self.speed))
+            .init_resource::<EventQueue>()
+            .init_resource::<GameState>()
+            .init_resource::<Diagnostics>()
+            .add_systems(Startup, (load_replay, setup_replay_ui))
+            .add_systems(Update, (
+                replay_speed_input_system,
+                replay_events_system.after(replay_speed_input_system),
+                update_replay_ui_system,
+            ));
+    }
+}
@@ -0,0 +1,407 @@
+use bevy::prelude::*;
+use chrono::{DateTime, Local, Utc};
+
+use crate::components::*;
+use crate::resources::{GameState, Office, Rooms, SessionInfo, SessionList, SHOWN_CLOSED_SESSIONS};
+use crate::systems::workspace::{room_label, SessionClosed};
+
+/// Keys that pin the first nine sessions in the list, live ones first
+const SESSION_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+const ROW_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.0);
+const FOCUSED_ROW_COLOR: Color = Color::srgba(0.25, 0.35, 0.55, 0.6);
+const HOVERED_ROW_COLOR: Color = Color::srgba(0.3, 0.3, 0.4, 0.5);
+
+/// System to set up the session sidebar, hidden until there is a second session
+pub fn setup_session_panel(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            // Below the replay or demo status in the corner
+            top: Val::Px(62.0),
+            right: Val::Px(10.0),
+            width: Val::Px(250.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.08, 0.85)),
+        Visibility::Hidden,
+        SessionPanel,
+    ))
+    .with_children(|panel| {
+        panel.spawn((
+            Button,
+            Node {
+                display: Display::None,
+                padding: UiRect::axes(Val::Px(4.0), Val::Px(2.0)),
+                ..default()
+            },
+            Text::new(""),
+            TextFont {
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.45, 0.45, 0.5)),
+            CollapsedSessionsRow,
+        ));
+    });
+}
+
+/// System to show or hide the sidebar with Tab
+pub fn toggle_session_panel_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    game_state: Res<GameState>,
+    mut session_list: ResMut<SessionList>,
+) {
+    if keys.just_pressed(KeyCode::Tab) {
+        let visible = session_list.is_visible(game_state.sessions.len());
+        session_list.shown = Some(!visible);
+    }
+}
+
+/// System to pin a session with keys 1 to 9, or follow the latest event again with 0
+pub fn session_keys_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    session_list: Res<SessionList>,
+    mut game_state: ResMut<GameState>,
+) {
+    if keys.just_pressed(KeyCode::Digit0) {
+        game_state.unpin();
+        return;
+    }
+
+    let Some(index) = SESSION_KEYS.iter().position(|key| keys.just_pressed(*key)) else {
+        return;
+    };
+    let (rows, _) = session_list.rows(&game_state);
+    let session_id = rows.get(index).map(|info| info.session_id.clone());
+    if let Some(session_id) = session_id {
+        game_state.pin(&session_id);
+    }
+}
+
+/// System to move sessions whose room has closed to the bottom of the list
+pub fn close_sessions_system(mut game_state: ResMut<GameState>, mut closed: EventReader<SessionClosed>) {
+    for event in closed.read() {
+        game_state.close(&event.session_id);
+    }
+}
+
+/// System to pin the session clicked in the sidebar; clicking the pinned one unpins it
+pub fn session_row_click_system(
+    mut game_state: ResMut<GameState>,
+    mut rows: Query<(&Interaction, &SessionRow, &mut BackgroundColor), Changed<Interaction>>,
+) {
+    for (interaction, row, mut background) in rows.iter_mut() {
+        match interaction {
+            Interaction::Pressed => {
+                if game_state.pinned && game_state.session_id.as_deref() == Some(row.session_id.as_str()) {
+                    game_state.unpin();
+                } else {
+                    game_state.pin(&row.session_id);
+                }
+            }
+            Interaction::Hovered => background.0 = HOVERED_ROW_COLOR,
+            // The next sidebar update restores the focus highlight
+            Interaction::None => background.0 = ROW_COLOR,
+        }
+    }
+}
+
+/// System to list or collapse the older closed sessions when their line is clicked
+pub fn collapsed_row_click_system(
+    mut session_list: ResMut<SessionList>,
+    rows: Query<&Interaction, (Changed<Interaction>, With<CollapsedSessionsRow>)>,
+) {
+    for interaction in rows.iter() {
+        if *interaction == Interaction::Pressed {
+            session_list.expanded = !session_list.expanded;
+        }
+    }
+}
+
+fn local_time(at: DateTime<Utc>) -> String {
+    at.with_timezone(&Local).format("%H:%M").to_string()
+}
+
+fn row_text(number: usize, info: &SessionInfo, subagents: usize, focused: bool, pinned: bool) -> String {
+    let marker = match (focused, pinned) {
+        (true, true) => "*",
+        (true, false) => ">",
+        _ => " ",
+    };
+    format!(
+        "{} {} {}\n    {} - {} | {} tools | {} subagents",
+        marker,
+        number,
+        room_label(&info.session_id, info.cwd.as_deref()),
+        local_time(info.started),
+        local_time(info.last_activity),
+        info.tool_calls,
+        subagents
+    )
+}
+
+/// The line standing in for older closed sessions, with what it says and whether it shows
+type CollapsedRow<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static mut Text, &'static mut Node),
+    (With<CollapsedSessionsRow>, Without<SessionRow>),
+>;
+
+/// System to keep the sidebar rows and room labels in step with the sessions
+#[allow(clippy::too_many_arguments)]
+pub fn update_session_panel_system(
+    mut commands: Commands,
+    game_state: Res<GameState>,
+    office: Res<Office>,
+    rooms: Res<Rooms>,
+    mut session_list: ResMut<SessionList>,
+    mut panel: Query<(Entity, &mut Visibility, Option<&Children>), With<SessionPanel>>,
+    mut rows: Query<(Entity, &SessionRow, &mut Text, &mut TextColor, &mut BackgroundColor, &Interaction)>,
+    mut collapsed_row: CollapsedRow,
+    mut labels: Query<&mut TextColor, (With<Text2d>, Without<SessionRow>)>,
+) {
+    if !game_state.is_changed() && !office.is_changed() && !rooms.is_changed() && !session_list.is_changed() {
+        return;
+    }
+
+    let Ok((panel, mut visibility, children)) = panel.get_single_mut() else {
+        return;
+    };
+    *visibility = if session_list.is_visible(game_state.sessions.len()) {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+
+    // Rows of collapsed sessions go, and new rows are filled in by the next update
+    let (listed, collapsed) = session_list.rows(&game_state);
+    let mut row_entities: Vec<(String, Entity)> = Vec::new();
+    for (entity, row, ..) in rows.iter() {
+        if listed.iter().any(|info| info.session_id == row.session_id) {
+            row_entities.push((row.session_id.clone(), entity));
+        } else {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    let mut added = false;
+    for info in listed.iter() {
+        if row_entities.iter().any(|(session_id, _)| *session_id == info.session_id) {
+            continue;
+        }
+        added = true;
+        let row = commands
+            .spawn((
+                Button,
+                Node {
+                    padding: UiRect::axes(Val::Px(4.0), Val::Px(2.0)),
+                    ..default()
+                },
+                Text::new(""),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.85, 0.85, 0.85)),
+                BackgroundColor(ROW_COLOR),
+                SessionRow {
+                    session_id: info.session_id.clone(),
+                },
+            ))
+            .id();
+        row_entities.push((info.session_id.clone(), row));
+    }
+    if added {
+        session_list.set_changed();
+    }
+
+    // Rows in the order of the list, which changes as rooms close, then the
+    // line standing in for the older closed sessions
+    let mut ordered: Vec<Entity> = listed
+        .iter()
+        .filter_map(|info| {
+            row_entities
+                .iter()
+                .find(|(session_id, _)| *session_id == info.session_id)
+                .map(|(_, entity)| *entity)
+        })
+        .collect();
+    if let Ok((entity, mut text, mut node)) = collapsed_row.get_single_mut() {
+        ordered.push(entity);
+        let closed = listed.iter().filter(|info| info.closed).count() + collapsed;
+        node.display = if closed > SHOWN_CLOSED_SESSIONS {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        **text = if collapsed > 0 {
+            format!("  + {} older sessions", collapsed)
+        } else {
+            "  - hide older sessions".to_string()
+        };
+    }
+    if children.is_none_or(|children| **children != ordered[..]) {
+        commands.entity(panel).replace_children(&ordered);
+    }
+
+    for (_, row, mut text, mut color, mut background, interaction) in rows.iter_mut() {
+        let Some(number) = listed.iter().position(|info| info.session_id == row.session_id) else {
+            continue;
+        };
+        let info = listed[number];
+        let focused = game_state.session_id.as_deref() == Some(info.session_id.as_str());
+        let subagents = office.0.get(&info.session_id).map_or(0, |state| state.subagents.len());
+
+        **text = row_text(number + 1, info, subagents, focused, game_state.pinned);
+        // Sessions whose room has faded out stay listed, dimmed
+        color.0 = if info.closed {
+            Color::srgb(0.45, 0.45, 0.5)
+        } else {
+            Color::srgb(0.85, 0.85, 0.85)
+        };
+        if *interaction == Interaction::None {
+            background.0 = if focused { FOCUSED_ROW_COLOR } else { ROW_COLOR };
+        }
+    }
+
+    // Pick out the focused room when there is more than one
+    for room in rooms.rooms.iter() {
+        let Ok(mut label) = labels.get_mut(room.label) else {
+            continue;
+        };
+        let focused = rooms.rooms.len() > 1 && room.session_id.is_some() && room.session_id == game_state.session_id;
+        let alpha = label.0.alpha();
+        label.0 = if focused {
+            Color::srgb(0.5, 0.8, 1.0)
+        } else {
+            Color::srgb(0.6, 0.6, 0.7)
+        }
+        .with_alpha(alpha);
+    }
+}
+
+/// System plugin for the session sidebar and picking which session to follow
+pub struct SessionPanelPlugin;
+
+impl Plugin for SessionPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameState>()
+            .init_resource::<SessionList>()
+            .add_systems(Startup, setup_session_panel)
+            .add_event::<SessionClosed>()
+            .add_systems(Update, (
+                toggle_session_panel_system,
+                close_sessions_system,
+                session_keys_system.after(close_sessions_system),
+                session_row_click_system,
+                collapsed_row_click_system,
+                update_session_panel_system
+                    .after(toggle_session_panel_system)
+                    .after(session_keys_system)
+                    .after(session_row_click_system)
+                    .after(collapsed_row_click_system),
+            ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::events::EventType;
+    use crate::harness::{tool, HeadlessApp};
+
+    fn focus(sim: &HeadlessApp) -> Option<&str> {
+        sim.game_state().session_id.as_deref()
+    }
+
+    #[test]
+    fn a_pinned_session_keeps_the_focus() {
+        let mut sim = HeadlessApp::new();
+        sim.app.add_plugins(SessionPanelPlugin);
+
+        sim.send(tool("session-a", EventType::PreToolUse, "Read", "toolu_a1"));
+        sim.send(tool("session-b", EventType::PreToolUse, "Bash", "toolu_b1"));
+        sim.tick(2);
+        assert_eq!(focus(&sim), Some("session-b"));
+        assert!(sim.app.world().resource::<SessionList>().is_visible(2));
+
+        sim.press(KeyCode::Digit1);
+        assert_eq!(focus(&sim), Some("session-a"));
+
+        // The noisy session no longer steals the focus
+        sim.send(tool("session-b", EventType::PostToolUse, "Bash", "toolu_b1"));
+        sim.tick(1);
+        assert_eq!(focus(&sim), Some("session-a"));
+        assert_eq!(sim.game_state().session("session-b").unwrap().tool_calls, 1);
+
+        sim.press(KeyCode::Digit0);
+        sim.send(tool("session-b", EventType::PreToolUse, "Edit", "toolu_b2"));
+        sim.tick(1);
+        assert_eq!(focus(&sim), Some("session-b"));
+    }
+
+    #[test]
+    fn sessions_with_a_room_are_numbered_first() {
+        let mut sim = HeadlessApp::new();
+        sim.app.add_plugins(SessionPanelPlugin);
+        sim.app.world_mut().resource_mut::<Config>().sessions.idle_timeout = 1.0;
+
+        sim.send(tool("session-a", EventType::PreToolUse, "Read", "toolu_a1"));
+        sim.tick(2);
+        // Keep the second session busy until the first one's room has gone
+        for index in 0..20 {
+            let id = format!("toolu_b{}", index);
+            sim.send(tool("session-b", EventType::PreToolUse, "Bash", &id));
+            sim.tick(15);
+        }
+        assert!(sim.game_state().session("session-a").unwrap().closed);
+
+        sim.press(KeyCode::Digit1);
+        assert_eq!(focus(&sim), Some("session-b"));
+        sim.press(KeyCode::Digit2);
+        assert_eq!(focus(&sim), Some("session-a"));
+    }
+
+    #[test]
+    fn every_session_is_kept_with_older_closed_ones_collapsed() {
+        let mut game_state = GameState::default();
+        let start = Utc::now();
+        let ids: Vec<String> = (0..SHOWN_CLOSED_SESSIONS + 3).map(|index| format!("session-{}", index)).collect();
+        for (minutes, session_id) in ids.iter().enumerate() {
+            game_state.session_entry(session_id, start + chrono::Duration::minutes(minutes as i64));
+        }
+        for session_id in &ids[1..] {
+            game_state.close(session_id);
+        }
+        assert_eq!(game_state.sessions.len(), ids.len());
+
+        // The open session, then the latest closed ones
+        let mut session_list = SessionList::default();
+        let (rows, collapsed) = session_list.rows(&game_state);
+        let shown: Vec<&str> = rows.iter().map(|info| info.session_id.as_str()).collect();
+        let mut expected = vec![ids[0].as_str()];
+        expected.extend(ids[3..].iter().rev().map(String::as_str));
+        assert_eq!(shown, expected);
+        assert_eq!(collapsed, 2);
+
+        session_list.expanded = true;
+        let (rows, collapsed) = session_list.rows(&game_state);
+        assert_eq!(rows.len(), ids.len());
+        assert_eq!(collapsed, 0);
+    }
+}
@@ -0,0 +1,259 @@
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+
+use crate::components::*;
+use crate::domain::AgentId;
+use crate::resources::{EventQueue, GameState, Office, Rooms, StationOccupancy, StationPositions, Timeline};
+use crate::sprites::SpriteAssets;
+use crate::systems::agent::spawn_subagent;
+use crate::systems::workspace::{room_label, spawn_room};
+
+/// System to rebuild the workspace at the position the timeline was moved to
+#[allow(clippy::too_many_arguments)]
+pub fn apply_seek_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut timeline: ResMut<Timeline>,
+    mut event_queue: ResMut<EventQueue>,
+    mut office: ResMut<Office>,
+    mut rooms: ResMut<Rooms>,
+    mut game_state: ResMut<GameState>,
+    mut station_occupancy: ResMut<StationOccupancy>,
+    station_positions: Res<StationPositions>,
+    sprite_assets: Res<SpriteAssets>,
+) {
+    let Some(target) = timeline.seek_to.take() else {
+        return;
+    };
+
+    // Split processed and pending events again at the new position
+    let mut events = std::mem::take(&mut timeline.history);
+    events.extend(event_queue.events.drain(..));
+    let target = target.min(events.len());
+    event_queue.events = events.split_off(target).into();
+    timeline.history = events;
+    timeline.forget_later_closures();
+
+    let sessions = timeline.replay(office.0.agent_types.clone());
+
+    for room in rooms.rooms.drain(..) {
+        commands.entity(room.entity).despawn_recursive();
+    }
+    station_occupancy.agents_at_station.clear();
+
+    for state in &sessions.sessions {
+        let session_id = state.session_id.clone().unwrap_or_default();
+        let mut room = spawn_room(
+            &mut commands,
+            &mut station_occupancy,
+            &station_positions,
+            &sprite_assets,
+            Some(session_id.clone()),
+            &room_label(&session_id, state.cwd.as_deref()),
+        );
+        room.open = state.office_open;
+        room.last_activity = time.elapsed_secs();
+
+        // Parents come before the subagents they started
+        let mut spawned: Vec<(AgentId, Entity)> = vec![(AgentId::Main, room.main_agent)];
+        for subagent in &state.subagents {
+            let parent = spawned
+                .iter()
+                .find(|(agent, _)| *agent == subagent.parent)
+                .map_or(room.main_agent, |(_, entity)| *entity);
+            let entity = spawn_subagent(
+                &mut commands,
+                &mut station_occupancy,
+                &station_positions,
+                &sprite_assets,
+                room.entity,
+                subagent.agent_type.clone(),
+                &subagent.tool_use_id,
+                subagent.station,
+                parent,
+            );
+            if let Some(entity) = entity {
+                spawned.push((AgentId::Subagent(subagent.tool_use_id.clone()), entity));
+            }
+        }
+
+        // Put the main agent straight where it was at that moment
+        let station = state.main_station;
+        station_occupancy.add_agent(room.entity, station, room.main_agent);
+        let position = station_positions.get(station)
+            + station_occupancy.get_orbital_offset(room.entity, station, room.main_agent);
+        commands.entity(room.main_agent).insert((
+            Transform::from_xyz(position.x, position.y, 10.0),
+            CurrentStation { station: Some(station) },
+        ));
+
+        rooms.rooms.push(room);
+    }
+
+    // What each session was doing at that moment
+    for info in game_state.sessions.iter_mut() {
+        let state = sessions.get(&info.session_id);
+        info.activity = state.and_then(|state| state.activity.clone());
+        info.closed = state.is_none();
+    }
+    // The focus follows the session of the latest event, as when playing
+    if let Some(latest) = timeline.history.last() {
+        game_state.follow(&latest.session_id);
+    }
+    game_state.agent_count = sessions.agent_count();
+    office.0 = sessions;
+}
+
+/// System for pause, step and seek from the keyboard
+pub fn timeline_input_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    event_queue: Res<EventQueue>,
+    mut timeline: ResMut<Timeline>,
+) {
+    if keys.just_pressed(KeyCode::Space) {
+        timeline.paused = !timeline.paused;
+        timeline.steps_requested = 0;
+    }
+
+    if !timeline.paused {
+        return;
+    }
+
+    // Step forward one event, but never further than what has been read
+    if keys.just_pressed(KeyCode::ArrowRight)
+        && timeline.steps_requested < event_queue.events.len()
+    {
+        timeline.steps_requested += 1;
+    }
+
+    if keys.just_pressed(KeyCode::ArrowLeft) && timeline.position() > 0 {
+        let target = timeline.position() - 1;
+        timeline.seek_to = Some(target);
+    }
+}
+
+/// System to seek by clicking or dragging on the timeline bar
+pub fn timeline_scrub_system(
+    event_queue: Res<EventQueue>,
+    mut timeline: ResMut<Timeline>,
+    track: Query<(&Interaction, &RelativeCursorPosition), With<TimelineTrack>>,
+) {
+    let Ok((interaction, cursor)) = track.get_single() else {
+        return;
+    };
+    if *interaction != Interaction::Pressed {
+        return;
+    }
+    let Some(normalized) = cursor.normalized else {
+        return;
+    };
+
+    let total = timeline.position() + event_queue.events.len();
+    let target = (normalized.x.clamp(0.0, 1.0) * total as f32).round() as usize;
+
+    // Scrubbing freezes playback at the chosen moment
+    timeline.paused = true;
+    timeline.steps_requested = 0;
+    if target != timeline.position() {
+        timeline.seek_to = Some(target);
+    }
+}
+
+/// System to set up the timeline bar above the status bar
+pub fn setup_timeline_ui(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Px(24.0),
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(40.0),
+                left: Val::Px(0.0),
+                padding: UiRect::horizontal(Val::Px(10.0)),
+                column_gap: Val::Px(10.0),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.1, 0.1, 0.1, 0.7)),
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        flex_grow: 1.0,
+                        height: Val::Px(8.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.25, 0.25, 0.3)),
+                    Interaction::default(),
+                    RelativeCursorPosition::default(),
+                    TimelineTrack,
+                ))
+                .with_children(|track| {
+                    track.spawn((
+                        Node {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.4, 0.7, 1.0)),
+                        TimelineFill,
+                    ));
+                });
+
+            parent.spawn((
+                Text::new("0/0"),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                TimelineText,
+            ));
+        });
+}
+
+/// System to keep the timeline bar in sync with the history
+pub fn update_timeline_ui_system(
+    timeline: Res<Timeline>,
+    event_queue: Res<EventQueue>,
+    mut fill: Query<&mut Node, With<TimelineFill>>,
+    mut text: Query<&mut Text, With<TimelineText>>,
+) {
+    let position = timeline.position();
+    let total = position + event_queue.events.len();
+
+    if let Ok(mut node) = fill.get_single_mut() {
+        let percent = if total == 0 {
+            0.0
+        } else {
+            position as f32 / total as f32 * 100.0
+        };
+        node.width = Val::Percent(percent);
+    }
+
+    if let Ok(mut text) = text.get_single_mut() {
+        let state = if timeline.paused { "Paused" } else { "Live" };
+        **text = format!("{} {}/{}", state, position, total);
+    }
+}
+
+/// System plugin for the event history timeline
+pub struct TimelinePlugin;
+
+impl Plugin for TimelinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Timeline>()
+            .init_resource::<EventQueue>()
+            .add_systems(Startup, setup_timeline_ui)
+            .add_systems(Update, (
+                timeline_input_system,
+                timeline_scrub_system,
+                apply_seek_system
+                    .after(timeline_input_system)
+                    .after(timeline_scrub_system)
+                    .before(crate::systems::agent::process_events_system),
+                update_timeline_ui_system,
+            ));
+    }
+}
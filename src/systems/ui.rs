@@ -55,6 +55,18 @@ pub fn setup_ui(mut commands: Commands) {
                 TextColor(Color::srgb(0.6, 0.6, 0.6)),
                 AgentCountText,
             ));
+
+            // Shown briefly after the events file is truncated or replaced
+            parent.spawn((
+                Text::new("[Log rotated]"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.7, 0.2)),
+                Visibility::Hidden,
+                LogRotatedText,
+            ));
         });
 
     // Title bar at the top
@@ -91,9 +103,11 @@ pub fn update_ui_system(
     mut session_text: Query<&mut Text, (With<SessionText>, Without<CurrentToolText>, Without<AgentCountText>)>,
     mut agent_text: Query<&mut Text, (With<AgentCountText>, Without<CurrentToolText>, Without<SessionText>)>,
 ) {
+    let focused = game_state.focused_session();
+
     // Update tool text
     if let Ok(mut text) = tool_text.get_single_mut() {
-        if let (Some(tool), Some(input)) = (&game_state.current_tool, &game_state.current_tool_input) {
+        if let Some((tool, input)) = focused.and_then(|info| info.activity.as_ref()) {
             **text = format!("[{}] {}", tool, input);
         } else {
             **text = "[Idle]".to_string();
@@ -108,13 +122,37 @@ pub fn update_ui_system(
             } else {
                 session
             };
-            **text = format!("Session: {}", short);
+            let pinned = if game_state.pinned { " (pinned)" } else { "" };
+            **text = format!("Session: {}{}", short, pinned);
         }
     }
 
     // Update agent count
     if let Ok(mut text) = agent_text.get_single_mut() {
-        **text = format!("Agents: {}", game_state.agent_count);
+        let tool_calls = focused.map_or(0, |info| info.tool_calls);
+        **text = format!("Agents: {} | Tools: {}", game_state.agent_count, tool_calls);
+    }
+}
+
+/// How long the "log rotated" marker stays visible
+const LOG_ROTATED_MARKER_SECS: f32 = 5.0;
+
+/// System to show the "log rotated" marker for a while after a rotation
+pub fn update_log_rotated_marker_system(
+    time: Res<Time>,
+    game_state: Res<GameState>,
+    mut marker: Query<&mut Visibility, With<LogRotatedText>>,
+) {
+    let recently_rotated = game_state
+        .log_rotated_at
+        .is_some_and(|at| time.elapsed_secs() - at < LOG_ROTATED_MARKER_SECS);
+
+    if let Ok(mut visibility) = marker.get_single_mut() {
+        *visibility = if recently_rotated {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
     }
 }
 
@@ -124,6 +162,6 @@ pub struct UIPlugin;
 impl Plugin for UIPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, setup_ui)
-            .add_systems(Update, update_ui_system);
+            .add_systems(Update, (update_ui_system, update_log_rotated_marker_system));
     }
 }
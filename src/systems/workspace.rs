@@ -0,0 +1,347 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use std::path::Path;
+
+use crate::components::*;
+use crate::config::Config;
+use crate::resources::{GameState, Office, Room, Rooms, StationOccupancy, StationPositions, Timeline};
+use crate::sprites::SpriteAssets;
+use crate::systems::agent::{process_events_system, spawn_main_agent};
+
+/// Space given to each room, a little more than its floor
+pub const ROOM_SIZE: Vec2 = Vec2::new(800.0, 600.0);
+
+/// How long an idle room takes to fade away
+const ROOM_FADE_SECS: f32 = 3.0;
+
+/// A session's room went quiet for long enough to be removed
+#[derive(Event, Debug, Clone)]
+pub struct SessionClosed {
+    pub session_id: String,
+}
+
+/// Name shown above a room: the project directory and the start of the session id
+pub fn room_label(session_id: &str, cwd: Option<&str>) -> String {
+    let short = session_id.get(..8).unwrap_or(session_id);
+    let project = cwd
+        .map(Path::new)
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy());
+    match project {
+        Some(project) => format!("{} ({})", project, short),
+        None => format!("Session {}", short),
+    }
+}
+
+/// Spawn a room with its floor, stations and main agent, labelled `label`
+pub fn spawn_room(
+    commands: &mut Commands,
+    station_occupancy: &mut StationOccupancy,
+    station_positions: &StationPositions,
+    sprite_assets: &SpriteAssets,
+    session_id: Option<String>,
+    label: &str,
+) -> Room {
+    let entity = commands
+        .spawn((Transform::default(), Visibility::default()))
+        .id();
+
+    let mut floor = Entity::PLACEHOLDER;
+    let mut label_entity = Entity::PLACEHOLDER;
+    commands.entity(entity).with_children(|parent| {
+        // Background workspace area - darker floor
+        floor = parent
+            .spawn((
+                Sprite {
+                    color: Color::srgba(0.15, 0.15, 0.2, 0.8),
+                    custom_size: Some(Vec2::new(750.0, 550.0)),
+                    ..default()
+                },
+                Transform::from_xyz(0.0, 0.0, 0.0),
+                OfficeFloor,
+            ))
+            .id();
+
+        // Grid pattern on floor
+        for i in -5..6 {
+            // Vertical lines
+            parent.spawn((
+                Sprite {
+                    color: Color::srgba(0.25, 0.25, 0.3, 0.3),
+                    custom_size: Some(Vec2::new(2.0, 550.0)),
+                    ..default()
+                },
+                Transform::from_xyz(i as f32 * 70.0, 0.0, 0.1),
+            ));
+            // Horizontal lines
+            parent.spawn((
+                Sprite {
+                    color: Color::srgba(0.25, 0.25, 0.3, 0.3),
+                    custom_size: Some(Vec2::new(750.0, 2.0)),
+                    ..default()
+                },
+                Transform::from_xyz(0.0, i as f32 * 55.0, 0.1),
+            ));
+        }
+
+        // Which session this room shows, above the meeting area
+        label_entity = parent
+            .spawn((
+                Text2d::new(label),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.6, 0.6, 0.7)),
+                Transform::from_xyz(0.0, 255.0, 2.0),
+            ))
+            .id();
+    });
+
+    // Spawn stations with pixel art sprites
+    for station_type in [
+        StationType::Library,
+        StationType::Desk,
+        StationType::Terminal,
+        StationType::WebPortal,
+        StationType::MeetingArea,
+    ] {
+        spawn_station(commands, entity, station_positions, sprite_assets, station_type);
+    }
+
+    let main_agent = spawn_main_agent(commands, station_occupancy, station_positions, sprite_assets, entity);
+
+    Room {
+        session_id,
+        entity,
+        floor,
+        label: label_entity,
+        main_agent,
+        // Sessions already running when the visualiser starts never send SessionStart
+        open: true,
+        last_activity: 0.0,
+        fading: false,
+    }
+}
+
+/// Spawn a workstation with pixel art
+fn spawn_station(
+    commands: &mut Commands,
+    room: Entity,
+    positions: &StationPositions,
+    sprite_assets: &SpriteAssets,
+    station_type: StationType,
+) {
+    let pos = positions.get(station_type);
+
+    // Get the sprite handle for this station
+    if let Some(image_handle) = sprite_assets.stations.get(&station_type) {
+        commands
+            .spawn((
+                Sprite {
+                    image: image_handle.clone(),
+                    custom_size: Some(Vec2::new(96.0, 72.0)), // Scale up 1.5x
+                    ..default()
+                },
+                Transform::from_xyz(pos.x, pos.y, 1.0),
+                Station { station_type },
+            ))
+            .set_parent(room)
+            .with_children(|parent| {
+                // Station label with background
+                parent.spawn((
+                    Sprite {
+                        color: Color::srgba(0.0, 0.0, 0.0, 0.7),
+                        custom_size: Some(Vec2::new(70.0, 20.0)),
+                        ..default()
+                    },
+                    Transform::from_xyz(0.0, -50.0, 1.5),
+                ));
+                parent.spawn((
+                    Text2d::new(station_type.label()),
+                    TextFont {
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                    Transform::from_xyz(0.0, -50.0, 2.0),
+                ));
+            });
+    }
+}
+
+/// System to keep an empty room on screen while no session is shown
+pub fn ensure_room_system(
+    mut commands: Commands,
+    mut rooms: ResMut<Rooms>,
+    mut game_state: ResMut<GameState>,
+    mut station_occupancy: ResMut<StationOccupancy>,
+    station_positions: Res<StationPositions>,
+    sprite_assets: Res<SpriteAssets>,
+) {
+    if !rooms.rooms.is_empty() {
+        return;
+    }
+
+    let room = spawn_room(
+        &mut commands,
+        &mut station_occupancy,
+        &station_positions,
+        &sprite_assets,
+        None,
+        "",
+    );
+    rooms.rooms.push(room);
+    game_state.agent_count = 1;
+}
+
+/// System to arrange the rooms in a grid and zoom the camera out to fit them all,
+/// or in on the pinned session's room
+pub fn layout_rooms_system(
+    rooms: Res<Rooms>,
+    game_state: Res<GameState>,
+    mut transforms: Query<&mut Transform, Without<Camera2d>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<(&mut OrthographicProjection, &mut Transform), With<Camera2d>>,
+) {
+    let count = rooms.rooms.len().max(1);
+    let columns = (count as f32).sqrt().ceil() as usize;
+    let rows = count.div_ceil(columns);
+
+    let mut focus = None;
+    for (index, room) in rooms.rooms.iter().enumerate() {
+        let column = (index % columns) as f32 - (columns - 1) as f32 / 2.0;
+        let row = (rows - 1) as f32 / 2.0 - (index / columns) as f32;
+        let position = Vec2::new(column * ROOM_SIZE.x, row * ROOM_SIZE.y);
+        if let Ok(mut transform) = transforms.get_mut(room.entity) {
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+        }
+        if game_state.pinned && room.session_id.is_some() && room.session_id == game_state.session_id {
+            focus = Some(position);
+        }
+    }
+
+    // One room fills the window as before; more zoom out
+    let (Ok(window), Ok((mut projection, mut camera))) = (windows.get_single(), cameras.get_single_mut()) else {
+        return;
+    };
+    let (centre, columns, rows) = match focus {
+        Some(position) => (position, 1, 1),
+        None => (Vec2::ZERO, columns, rows),
+    };
+    let scale = (columns as f32 * ROOM_SIZE.x / window.width())
+        .max(rows as f32 * ROOM_SIZE.y / window.height())
+        .max(1.0);
+    if projection.scale != scale {
+        projection.scale = scale;
+    }
+    if camera.translation.truncate() != centre {
+        camera.translation.x = centre.x;
+        camera.translation.y = centre.y;
+    }
+}
+
+/// System to fade out rooms whose session has gone quiet, then remove them
+#[allow(clippy::too_many_arguments)]
+pub fn fade_idle_rooms_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<Config>,
+    mut timeline: ResMut<Timeline>,
+    mut rooms: ResMut<Rooms>,
+    mut office: ResMut<Office>,
+    mut station_occupancy: ResMut<StationOccupancy>,
+    mut closed_sessions: EventWriter<SessionClosed>,
+    children: Query<&Children>,
+    mut faded: Query<(Option<&mut Sprite>, Option<&mut TextColor>, Option<&FadeBase>)>,
+) {
+    // Time spent paused does not count as idle, or a long pause would close every room
+    if timeline.paused {
+        let paused_for = time.delta_secs();
+        for room in rooms.rooms.iter_mut() {
+            room.last_activity += paused_for;
+        }
+        return;
+    }
+
+    let now = time.elapsed_secs();
+    let timeout = config.sessions.idle_timeout;
+
+    let mut closed = Vec::new();
+    for room in rooms.rooms.iter_mut() {
+        // The empty room waits for a session however long it takes
+        let Some(session_id) = &room.session_id else {
+            continue;
+        };
+
+        let idle = now - room.last_activity;
+        if idle < timeout && !room.fading {
+            continue;
+        }
+
+        let fade = 1.0 - ((idle - timeout) / ROOM_FADE_SECS).clamp(0.0, 1.0);
+        if fade <= 0.0 {
+            closed.push((room.entity, session_id.clone()));
+            continue;
+        }
+
+        for entity in children.iter_descendants(room.entity) {
+            let Ok((sprite, text_color, base)) = faded.get_mut(entity) else {
+                continue;
+            };
+            let original = match (sprite, text_color) {
+                (Some(mut sprite), _) => {
+                    let original = base.map_or(sprite.color.alpha(), |base| base.0);
+                    sprite.color.set_alpha(original * fade);
+                    original
+                }
+                (None, Some(mut text_color)) => {
+                    let original = base.map_or(text_color.0.alpha(), |base| base.0);
+                    text_color.0.set_alpha(original * fade);
+                    original
+                }
+                (None, None) => continue,
+            };
+            if fade >= 1.0 {
+                commands.entity(entity).remove::<FadeBase>();
+            } else if base.is_none() {
+                // Bubbles can expire while the room fades
+                commands.entity(entity).try_insert(FadeBase(original));
+            }
+        }
+
+        // Activity came back before the room was gone
+        room.fading = fade < 1.0;
+    }
+
+    for (entity, session_id) in closed {
+        info!("Session {} went quiet; removing its room", session_id);
+        commands.entity(entity).despawn_recursive();
+        station_occupancy.remove_room(entity);
+        rooms.rooms.retain(|room| room.entity != entity);
+        office.0.close(&session_id);
+        timeline.close(&session_id);
+        closed_sessions.send(SessionClosed { session_id });
+    }
+}
+
+/// System plugin for the rooms of the office and their workstations
+pub struct WorkspacePlugin;
+
+impl Plugin for WorkspacePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StationPositions>()
+            .init_resource::<StationOccupancy>()
+            .init_resource::<Rooms>()
+            .init_resource::<Office>()
+            .init_resource::<Config>()
+            .init_resource::<GameState>()
+            .add_event::<SessionClosed>()
+            .add_systems(Update, (
+                ensure_room_system.before(process_events_system),
+                layout_rooms_system.after(process_events_system),
+                fade_idle_rooms_system.after(process_events_system),
+            ));
+    }
+}
@@ -0,0 +1,194 @@
+use std::fs::{File, Metadata};
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::sources::{push_line, SourceEvent};
+
+/// Incremental reader for an append-only JSONL file.
+///
+/// The open handle is kept between reads so that when the file is rotated by
+/// renaming, whatever was appended to the old file is still drained before
+/// switching over to the new one. A trailing line without its newline yet is
+/// held back until the writer finishes it.
+pub struct LogTail {
+    path: PathBuf,
+    position: u64,
+    reader: Option<BufReader<File>>,
+    /// Identity of the file behind `reader`, used to notice replacement
+    file_id: Option<FileId>,
+    /// Bytes of a line whose newline has not been written yet
+    partial: Vec<u8>,
+    /// Number of complete lines before `position`, less those before `uncounted_start`
+    line_number: u64,
+    /// Where reading started, while the lines before it are still to be counted
+    uncounted_start: Option<u64>,
+    /// Last error reported, so a persistent failure is reported once
+    last_error: Option<String>,
+}
+
+impl LogTail {
+    /// Start reading at `position`. Counting the lines before it is left to
+    /// the first read, so opening a large file costs nothing up front.
+    pub fn new(path: PathBuf, position: u64) -> Self {
+        LogTail {
+            path,
+            position,
+            reader: None,
+            file_id: None,
+            partial: Vec::new(),
+            line_number: 0,
+            uncounted_start: (position > 0).then_some(position),
+            last_error: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read everything that happened to the file since the last call
+    pub fn read_new(&mut self) -> Vec<SourceEvent> {
+        let mut events = Vec::new();
+
+        if self.reader.is_none() {
+            match self.open() {
+                Ok(()) => {}
+                // Not written yet, which is not worth reporting
+                Err(err) if err.kind() == ErrorKind::NotFound => return events,
+                Err(err) => {
+                    self.report_error(&mut events, err);
+                    return events;
+                }
+            }
+        }
+
+        // Finish whatever is left in the file we already have open
+        self.drain_lines(&mut events);
+
+        let Ok(metadata) = std::fs::metadata(&self.path) else {
+            // Moved away and not recreated yet; keep the old handle until it is
+            return events;
+        };
+
+        let replaced = match (self.file_id, file_id(&metadata)) {
+            (Some(old), Some(new)) => old != new,
+            _ => false,
+        };
+        let truncated = metadata.len() < self.position;
+
+        if replaced || truncated {
+            // The old file is finished, so its last line will never be completed
+            if !self.partial.is_empty() {
+                self.line_number += 1;
+                let fragment = std::mem::take(&mut self.partial);
+                push_line(&mut events, &fragment, self.line_number);
+            }
+
+            self.reader = None;
+            self.position = 0;
+            self.line_number = 0;
+            self.uncounted_start = None;
+            events.push(SourceEvent::Rotated);
+
+            match self.open() {
+                Ok(()) => self.drain_lines(&mut events),
+                Err(err) => self.report_error(&mut events, err),
+            }
+        }
+
+        events
+    }
+
+    /// Open the file at the current position
+    fn open(&mut self) -> io::Result<()> {
+        let file = File::open(&self.path)?;
+        let metadata = file.metadata()?;
+
+        // A file that shrank while nobody was reading it starts over
+        if metadata.len() < self.position {
+            self.position = 0;
+            self.line_number = 0;
+            self.uncounted_start = None;
+        }
+
+        let mut reader = BufReader::new(file);
+        reader.seek(SeekFrom::Start(self.position))?;
+
+        self.file_id = file_id(&metadata);
+        self.reader = Some(reader);
+        self.last_error = None;
+        Ok(())
+    }
+
+    /// Pass an error on, unless it is the same one as last time
+    fn report_error(&mut self, events: &mut Vec<SourceEvent>, err: io::Error) {
+        let message = format!("{}: {}", self.path.display(), err);
+        if self.last_error.as_ref() != Some(&message) {
+            events.push(SourceEvent::Error(message.clone()));
+            self.last_error = Some(message);
+        }
+    }
+
+    fn drain_lines(&mut self, events: &mut Vec<SourceEvent>) {
+        let Some(reader) = self.reader.as_mut() else {
+            return;
+        };
+
+        if let Some(start) = self.uncounted_start.take() {
+            self.line_number += count_lines(&self.path, start).unwrap_or(0);
+        }
+
+        // Read raw bytes so a multi-byte character split mid-write stays intact
+        let error = loop {
+            let bytes_read = match reader.read_until(b'\n', &mut self.partial) {
+                Ok(0) => break None,
+                Ok(bytes_read) => bytes_read,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => break Some(err),
+            };
+            self.position += bytes_read as u64;
+
+            if self.partial.last() != Some(&b'\n') {
+                // Caught mid-write; the rest arrives with a later read
+                break None;
+            }
+
+            self.line_number += 1;
+            push_line(events, &self.partial, self.line_number);
+            self.partial.clear();
+        };
+
+        if let Some(err) = error {
+            self.report_error(events, err);
+        }
+    }
+}
+
+/// Count the lines in the first `limit` bytes of a file
+fn count_lines(path: &Path, limit: u64) -> io::Result<u64> {
+    let mut reader = BufReader::new(File::open(path)?.take(limit));
+    let mut count = 0;
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(count);
+        }
+        count += buffer.iter().filter(|&&byte| byte == b'\n').count() as u64;
+        let consumed = buffer.len();
+        reader.consume(consumed);
+    }
+}
+
+/// Device and inode pair identifying a file independent of its path
+type FileId = (u64, u64);
+
+#[cfg(unix)]
+fn file_id(metadata: &Metadata) -> Option<FileId> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &Metadata) -> Option<FileId> {
+    None
+}
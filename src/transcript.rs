@@ -0,0 +1,361 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::archive;
+use crate::events::{EventType, ToolEvent};
+
+/// One line of a Claude Code transcript; only the parts needed for backfill
+#[derive(Debug, Deserialize)]
+struct TranscriptEntry {
+    #[serde(default)]
+    timestamp: Option<DateTime<Utc>>,
+    /// Set on messages that belong to a subagent's own conversation
+    #[serde(default, rename = "isSidechain")]
+    is_sidechain: bool,
+    #[serde(default)]
+    cwd: String,
+    #[serde(default)]
+    uuid: Option<String>,
+    /// Previous message in the same conversation; `None` at the start of one
+    #[serde(default, rename = "parentUuid")]
+    parent_uuid: Option<String>,
+    message: Option<TranscriptMessage>,
+}
+
+impl TranscriptEntry {
+    /// The tool calls and results in the message; none for plain text
+    fn blocks(&self) -> Vec<ContentBlock> {
+        self.message
+            .as_ref()
+            .and_then(|message| Vec::<ContentBlock>::deserialize(&message.content).ok())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TranscriptMessage {
+    /// Plain string for typed user prompts, a list of blocks otherwise
+    #[serde(default)]
+    content: serde_json::Value,
+}
+
+impl TranscriptMessage {
+    /// Text of a message, which for the first message of a subagent is the Task prompt
+    fn text(&self) -> Option<String> {
+        if let Some(text) = self.content.as_str() {
+            return Some(text.to_string());
+        }
+        let text: Vec<&str> = self
+            .content
+            .as_array()?
+            .iter()
+            .filter_map(|block| block["text"].as_str())
+            .collect();
+        (!text.is_empty()).then(|| text.join("\n"))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlock {
+    ToolUse {
+        id: String,
+        name: String,
+        #[serde(default)]
+        input: serde_json::Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// A tool call found in the transcript
+struct ToolCall {
+    id: String,
+    name: String,
+    input: serde_json::Value,
+    timestamp: DateTime<Utc>,
+    cwd: String,
+    is_sidechain: bool,
+}
+
+/// Which agent made each tool call in a transcript.
+///
+/// A subagent's messages are written to its session's transcript as a
+/// sidechain: a conversation of its own, started by the Task prompt. Following
+/// each message back to the start of its conversation and matching that prompt
+/// against the Task calls seen so far tells which Task a tool call ran under.
+/// Tasks given the same prompt are matched to their subagents in the order
+/// they were called.
+#[derive(Debug, Default)]
+pub struct Callers {
+    /// Bytes of the transcript already read
+    offset: u64,
+    /// Task prompt to the ids of the Task calls whose subagent has not started yet, oldest first
+    tasks: HashMap<String, VecDeque<String>>,
+    /// Message uuid to the Task whose subagent wrote it, `None` for the main agent.
+    /// Sidechain messages whose Task could not be told are left out.
+    owners: HashMap<String, Option<String>>,
+    /// Tool call id to the Task it was made inside, `None` for the main agent.
+    /// Calls whose agent could not be told are left out.
+    calls: HashMap<String, Option<String>>,
+}
+
+impl Callers {
+    /// Read what was appended to the transcript since the last update
+    pub fn update(&mut self, path: &Path) -> std::io::Result<()> {
+        let mut file = std::fs::File::open(path)?;
+        if file.metadata()?.len() < self.offset {
+            // Rewritten from scratch
+            *self = Callers::default();
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut appended = Vec::new();
+        file.read_to_end(&mut appended)?;
+
+        // A line still being written is read again next time, so a character
+        // cut off mid-write is never decoded
+        let complete = appended.iter().rposition(|&byte| byte == b'\n').map_or(0, |end| end + 1);
+        for line in String::from_utf8_lossy(&appended[..complete]).lines() {
+            if let Ok(entry) = serde_json::from_str::<TranscriptEntry>(line) {
+                self.read_entry(&entry, &entry.blocks());
+            }
+        }
+        self.offset += complete as u64;
+        Ok(())
+    }
+
+    /// The Task a tool call was made inside: `Some(None)` for the main agent,
+    /// `None` if the call is not in the transcript or its agent is unknown
+    pub fn caller(&self, tool_use_id: &str) -> Option<Option<&str>> {
+        self.calls.get(tool_use_id).map(Option::as_deref)
+    }
+
+    /// The oldest Task given `prompt` whose subagent had not started yet
+    fn take_task(&mut self, prompt: &str) -> Option<String> {
+        let pending = self.tasks.get_mut(prompt)?;
+        let task = pending.pop_front();
+        if pending.is_empty() {
+            self.tasks.remove(prompt);
+        }
+        task
+    }
+
+    /// Note who wrote `entry` and who made the tool calls among its `blocks`
+    fn read_entry(&mut self, entry: &TranscriptEntry, blocks: &[ContentBlock]) {
+        let Some(message) = &entry.message else {
+            return;
+        };
+
+        // `None` when a sidechain cannot be traced back to its Task, which
+        // must not be mistaken for the main agent
+        let owner = if !entry.is_sidechain {
+            Some(None)
+        } else if let Some(parent) = &entry.parent_uuid {
+            self.owners.get(parent).cloned()
+        } else {
+            message
+                .text()
+                .and_then(|prompt| self.take_task(&prompt))
+                .map(Some)
+        };
+        if let Some(uuid) = &entry.uuid
+            && let Some(owner) = &owner
+        {
+            self.owners.insert(uuid.clone(), owner.clone());
+        }
+
+        for block in blocks {
+            if let ContentBlock::ToolUse { id, name, input } = block {
+                if name == "Task"
+                    && let Some(prompt) = input["prompt"].as_str()
+                {
+                    self.tasks.entry(prompt.to_string()).or_default().push_back(id.clone());
+                }
+                if let Some(owner) = &owner {
+                    self.calls.insert(id.clone(), owner.clone());
+                }
+            }
+        }
+    }
+}
+
+/// State recovered from a transcript, for attaching to a session mid-flight
+#[derive(Debug, Default)]
+pub struct Backfill {
+    /// Synthetic PreToolUse events that recreate the live state when processed:
+    /// one per Task still running, the latest tool of each of their subagents,
+    /// then the main agent's latest tool
+    pub events: Vec<ToolEvent>,
+    /// Tool calls made so far, including those inside subagents
+    pub tool_calls: usize,
+}
+
+/// Read a session's transcript, compressed or not, and work out what is still in flight
+pub fn backfill(session_id: &str, path: &Path) -> std::io::Result<Backfill> {
+    let contents = archive::read_log(path)?;
+    Ok(backfill_from(session_id, &path.display().to_string(), &contents))
+}
+
+/// Work out what is still in flight from the `contents` of the transcript at `transcript_path`
+fn backfill_from(session_id: &str, transcript_path: &str, contents: &[u8]) -> Backfill {
+    let mut calls = Vec::new();
+    let mut finished = HashSet::new();
+    let mut callers = Callers::default();
+
+    // A live transcript may end partway through a character being written
+    for line in String::from_utf8_lossy(contents).lines() {
+        // Transcripts hold plenty of entry types this does not model; skip them
+        let Ok(entry) = serde_json::from_str::<TranscriptEntry>(line) else {
+            continue;
+        };
+        let blocks = entry.blocks();
+        callers.read_entry(&entry, &blocks);
+
+        for block in blocks {
+            match block {
+                ContentBlock::ToolUse { id, name, input } => calls.push(ToolCall {
+                    id,
+                    name,
+                    input,
+                    timestamp: entry.timestamp.unwrap_or_else(Utc::now),
+                    cwd: entry.cwd.clone(),
+                    is_sidechain: entry.is_sidechain,
+                }),
+                ContentBlock::ToolResult { tool_use_id } => {
+                    finished.insert(tool_use_id);
+                }
+                ContentBlock::Other => {}
+            }
+        }
+    }
+
+    let tool_calls = calls.len();
+
+    // `None` when the agent is unknown, `Some("")` for the main agent
+    let owner = |call: &ToolCall| {
+        callers
+            .caller(&call.id)
+            .map(|task| task.unwrap_or_default().to_string())
+    };
+
+    let running_tasks: Vec<&ToolCall> = calls
+        .iter()
+        .filter(|call| call.name == "Task" && !finished.contains(&call.id))
+        .collect();
+    let latest_subagent_tools = running_tasks.iter().filter_map(|task| {
+        calls
+            .iter()
+            .rev()
+            .find(|call| call.name != "Task" && owner(call).as_deref() == Some(task.id.as_str()))
+    });
+    let latest_main_tool = calls
+        .iter()
+        .rev()
+        .find(|call| !call.is_sidechain && call.name != "Task");
+
+    let events = running_tasks
+        .iter()
+        .copied()
+        .chain(latest_subagent_tools)
+        .chain(latest_main_tool)
+        .map(|call| ToolEvent {
+            timestamp: call.timestamp,
+            session_id: session_id.to_string(),
+            event_type: EventType::PreToolUse,
+            tool_name: call.name.clone(),
+            tool_use_id: call.id.clone(),
+            cwd: call.cwd.clone(),
+            transcript_path: transcript_path.to_string(),
+            subagent_type: String::new(),
+            subagent_prompt: String::new(),
+            prompt: String::new(),
+            message: String::new(),
+            tool_input: Some(call.input.clone()),
+            tool_response: None,
+            parent_tool_use_id: owner(call),
+        })
+        .collect();
+
+    Backfill { events, tool_calls }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION: &str = "session-a";
+
+    fn read(lines: &[&str]) -> Callers {
+        let mut callers = Callers::default();
+        for line in lines {
+            let entry: TranscriptEntry = serde_json::from_str(line).unwrap();
+            callers.read_entry(&entry, &entry.blocks());
+        }
+        callers
+    }
+
+    #[test]
+    fn sidechain_calls_belong_to_the_task_whose_prompt_started_them() {
+        let lines = [
+            r#"{"uuid":"m1","parentUuid":null,"message":{"content":[{"type":"tool_use","id":"toolu_task","name":"Task","input":{"prompt":"Find the config loader"}}]}}"#,
+            r#"{"uuid":"s1","parentUuid":null,"isSidechain":true,"message":{"content":"Find the config loader"}}"#,
+            r#"{"uuid":"s2","parentUuid":"s1","isSidechain":true,"message":{"content":[{"type":"tool_use","id":"toolu_grep","name":"Grep","input":{}}]}}"#,
+            r#"{"uuid":"m2","parentUuid":"m1","message":{"content":[{"type":"tool_use","id":"toolu_read","name":"Read","input":{}}]}}"#,
+        ];
+        let callers = read(&lines);
+
+        assert_eq!(callers.caller("toolu_grep"), Some(Some("toolu_task")));
+        assert_eq!(callers.caller("toolu_task"), Some(None));
+        assert_eq!(callers.caller("toolu_read"), Some(None));
+        assert_eq!(callers.caller("toolu_unknown"), None);
+    }
+
+    #[test]
+    fn tasks_with_the_same_prompt_are_matched_in_the_order_they_were_called() {
+        let lines = [
+            r#"{"uuid":"m1","parentUuid":null,"message":{"content":[{"type":"tool_use","id":"toolu_first","name":"Task","input":{"prompt":"Review the diff"}},{"type":"tool_use","id":"toolu_second","name":"Task","input":{"prompt":"Review the diff"}}]}}"#,
+            r#"{"uuid":"a1","parentUuid":null,"isSidechain":true,"message":{"content":"Review the diff"}}"#,
+            r#"{"uuid":"b1","parentUuid":null,"isSidechain":true,"message":{"content":"Review the diff"}}"#,
+            r#"{"uuid":"b2","parentUuid":"b1","isSidechain":true,"message":{"content":[{"type":"tool_use","id":"toolu_grep","name":"Grep","input":{}}]}}"#,
+            r#"{"uuid":"a2","parentUuid":"a1","isSidechain":true,"message":{"content":[{"type":"tool_use","id":"toolu_read","name":"Read","input":{}}]}}"#,
+        ];
+        let callers = read(&lines);
+
+        assert_eq!(callers.caller("toolu_read"), Some(Some("toolu_first")));
+        assert_eq!(callers.caller("toolu_grep"), Some(Some("toolu_second")));
+    }
+
+    #[test]
+    fn sidechain_calls_of_an_unknown_task_are_not_given_to_the_main_agent() {
+        let lines = [
+            r#"{"uuid":"m1","parentUuid":null,"message":{"content":[{"type":"tool_use","id":"toolu_task","name":"Task","input":{"prompt":"Find the config loader"}}]}}"#,
+            r#"{"uuid":"s1","parentUuid":null,"isSidechain":true,"message":{"content":"Something else entirely"}}"#,
+            r#"{"uuid":"s2","parentUuid":"s1","isSidechain":true,"message":{"content":[{"type":"tool_use","id":"toolu_grep","name":"Grep","input":{}}]}}"#,
+            r#"{"uuid":"s3","parentUuid":"gone","isSidechain":true,"message":{"content":[{"type":"tool_use","id":"toolu_glob","name":"Glob","input":{}}]}}"#,
+        ];
+        let callers = read(&lines);
+
+        // Left for the in-flight Task context to decide
+        assert_eq!(callers.caller("toolu_grep"), None);
+        assert_eq!(callers.caller("toolu_glob"), None);
+        assert_eq!(callers.caller("toolu_task"), Some(None));
+    }
+
+    #[test]
+    fn bad_bytes_and_a_line_cut_off_mid_character_do_not_spoil_the_backfill() {
+        let mut contents = Vec::new();
+        contents.extend_from_slice(br#"{"message":{"content":[{"type":"tool_use","id":"toolu_task","name":"Task","input":{"prompt":"Look"}}]}}"#);
+        contents.extend_from_slice(b"\n{\"message\":{\"content\":\"caf\xff\"}}\n{\"message\":{\"content\":\"\xe2\x82");
+
+        let backfill = backfill_from(SESSION, "transcript.jsonl", &contents);
+        assert_eq!(backfill.tool_calls, 1);
+        assert_eq!(backfill.events.len(), 1);
+        assert_eq!(backfill.events[0].tool_use_id, "toolu_task");
+    }
+}